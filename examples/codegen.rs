@@ -0,0 +1,32 @@
+//! Functions whose optimized code is inspected by `tests/codegen.rs`.
+
+#[macro_use]
+extern crate refmove;
+
+use refmove::{Anchor, AnchorExt, RefMove};
+
+pub struct Message(pub String);
+
+#[no_mangle]
+#[inline(never)]
+pub fn codegen_consume(x: RefMove<Message>) {
+    println!("{}", x.0);
+}
+
+#[no_mangle]
+#[inline(never)]
+pub fn codegen_anchor(x: Message) {
+    codegen_consume(x.anchor().borrow_move());
+}
+
+#[no_mangle]
+#[inline(never)]
+pub fn codegen_let_move(x: Message) {
+    let_move!(x = x);
+    codegen_consume(x);
+}
+
+fn main() {
+    codegen_anchor(Message("hoge".to_string()));
+    codegen_let_move(Message("fuga".to_string()));
+}
@@ -0,0 +1,246 @@
+//! Derive macros for [`refmove`][refmove].
+//!
+//! Use them through the `derive` feature of `refmove`.
+//!
+//! [refmove]: https://crates.io/crates/refmove
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro2::{Span, TokenStream};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, GenericParam, Generics, Ident,
+    Index, Lifetime, LifetimeParam, Member, Meta,
+};
+
+/// Derives `MoveFields`, which splits `RefMove<'a, Self>` into by-move
+/// references to the fields.
+///
+/// For a struct or enum `Foo`, it generates a struct or enum `FooFields<'a>`
+/// with the same shape as `Foo`, in which every field of type `T` is replaced
+/// with `RefMove<'a, T>`.
+///
+/// The derive is rejected for types implementing `Drop`, because their
+/// fields cannot be moved out individually.
+#[proc_macro_derive(MoveFields)]
+pub fn derive_move_fields(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match move_fields(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => compile_error(&e).into(),
+    }
+}
+
+/// Like `Error::to_compile_error` but also works in Rust 2015 crates,
+/// where `::core` is not in scope.
+fn compile_error(e: &Error) -> TokenStream {
+    let message = e.to_string();
+    quote_spanned!(e.span()=> compile_error!(#message);)
+}
+
+fn move_fields(input: &DeriveInput) -> Result<TokenStream, Error> {
+    check_repr(&input.attrs)?;
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let fields_name = Ident::new(&format!("{}Fields", name), name.span());
+    let lt = Lifetime::new("'__refmove", Span::call_site());
+    let generics = add_lifetime(&input.generics, &lt);
+    let (self_impl_generics, ty_generics, self_where_clause) = input.generics.split_for_impl();
+    let (impl_generics, fields_ty_generics, where_clause) = generics.split_for_impl();
+    let doc = format!("By-move references to the fields of `{}`.", name);
+
+    let (definition, body) = match input.data {
+        Data::Struct(ref data) => {
+            if data.fields.is_empty() {
+                return Err(Error::new(
+                    input.span(),
+                    "MoveFields cannot be derived for structs without fields",
+                ));
+            }
+            let decls = field_decls(&data.fields, &lt);
+            let members = members(&data.fields);
+            let definition = match data.fields {
+                Fields::Named(_) => quote! {
+                    #[doc = #doc]
+                    #vis struct #fields_name #impl_generics #where_clause #decls
+                },
+                _ => quote! {
+                    #[doc = #doc]
+                    #vis struct #fields_name #impl_generics #decls #where_clause;
+                },
+            };
+            let body = quote! {
+                #fields_name {
+                    #(#members: ::refmove::RefMove::from_ptr(&mut (*ptr).#members),)*
+                }
+            };
+            (definition, body)
+        }
+        Data::Enum(ref data) => {
+            if data
+                .variants
+                .iter()
+                .all(|variant| variant.fields.is_empty())
+            {
+                return Err(Error::new(
+                    input.span(),
+                    "MoveFields cannot be derived for enums without fields",
+                ));
+            }
+            let variants = data.variants.iter().map(|variant| {
+                let docs = docs(&variant.attrs);
+                let ident = &variant.ident;
+                let decls = field_decls(&variant.fields, &lt);
+                quote!(#(#docs)* #ident #decls)
+            });
+            let definition = quote! {
+                #[doc = #doc]
+                #vis enum #fields_name #impl_generics #where_clause {
+                    #(#variants,)*
+                }
+            };
+            let arms = data.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                let members = members(&variant.fields);
+                let bindings = bindings(&variant.fields);
+                quote! {
+                    #name::#ident { #(#members: ref mut #bindings,)* } => #fields_name::#ident {
+                        #(#members: ::refmove::RefMove::from_ptr(#bindings),)*
+                    },
+                }
+            });
+            let body = quote! {
+                match *ptr {
+                    #(#arms)*
+                }
+            };
+            (definition, body)
+        }
+        Data::Union(_) => {
+            return Err(Error::new(
+                input.span(),
+                "MoveFields cannot be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        #definition
+
+        // Conflicts with `impl<T: Drop> __MustNotImplDrop for T` if
+        // `Self: Drop`, whose fields cannot be moved out individually.
+        impl #self_impl_generics ::refmove::__MustNotImplDrop for #name #ty_generics #self_where_clause {}
+
+        impl #impl_generics ::refmove::MoveFields<#lt> for #name #ty_generics #where_clause {
+            type Fields = #fields_name #fields_ty_generics;
+
+            fn move_fields(this: ::refmove::RefMove<#lt, Self>) -> Self::Fields {
+                let ptr = ::refmove::RefMove::into_ptr(this);
+                unsafe { #body }
+            }
+        }
+    })
+}
+
+fn docs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .collect()
+}
+
+/// Field declarations with every type `T` replaced with `RefMove<'lt, T>`,
+/// including the surrounding braces or parentheses.
+fn field_decls(fields: &Fields, lt: &Lifetime) -> TokenStream {
+    let decls = fields.iter().map(|field| {
+        let docs = docs(&field.attrs);
+        let vis = &field.vis;
+        let ident = field.ident.iter();
+        let ty = &field.ty;
+        quote!(#(#docs)* #vis #(#ident:)* ::refmove::RefMove<#lt, #ty>)
+    });
+    match *fields {
+        Fields::Named(_) => quote!({ #(#decls,)* }),
+        Fields::Unnamed(_) => quote!((#(#decls,)*)),
+        Fields::Unit => quote!(),
+    }
+}
+
+fn members(fields: &Fields) -> Vec<Member> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match field.ident {
+            Some(ref ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        })
+        .collect()
+}
+
+fn bindings(fields: &Fields) -> Vec<Ident> {
+    (0..fields.len())
+        .map(|i| Ident::new(&format!("__field{}", i), Span::call_site()))
+        .collect()
+}
+
+/// Rejects `#[repr(packed)]`, whose fields may be unaligned.
+fn check_repr(attrs: &[Attribute]) -> Result<(), Error> {
+    for attr in attrs {
+        if !attr.path().is_ident("repr") {
+            continue;
+        }
+        if let Meta::List(ref list) = attr.meta {
+            let mut packed = false;
+            list.parse_nested_meta(|meta| {
+                if meta.path.is_ident("packed") {
+                    packed = true;
+                }
+                if meta.input.peek(syn::token::Paren) {
+                    let _: TokenStream = meta.input.parse()?;
+                }
+                Ok(())
+            })?;
+            if packed {
+                return Err(Error::new(
+                    attr.span(),
+                    "MoveFields cannot be derived for packed structs",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prepends `lt` to the generics and requires every other parameter to
+/// outlive it.
+fn add_lifetime(generics: &Generics, lt: &Lifetime) -> Generics {
+    let mut generics = generics.clone();
+    let bounds = generics
+        .params
+        .iter()
+        .filter_map(|param| match *param {
+            GenericParam::Lifetime(ref param) => {
+                let param = &param.lifetime;
+                Some(quote!(#param: #lt))
+            }
+            GenericParam::Type(ref param) => {
+                let param = &param.ident;
+                Some(quote!(#param: #lt))
+            }
+            GenericParam::Const(_) => None,
+        })
+        .collect::<Vec<_>>();
+    generics
+        .params
+        .insert(0, GenericParam::Lifetime(LifetimeParam::new(lt.clone())));
+    let where_clause = generics.make_where_clause();
+    for bound in bounds {
+        where_clause.predicates.push(syn::parse2(bound).unwrap());
+    }
+    generics
+}
@@ -2,8 +2,33 @@
 //!
 //! Anchors ensure validity of memory regions at caller side.
 
+#[cfg(all(feature = "alloc", feature = "nightly"))]
+use alloc::alloc::{Allocator, Global};
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::rc::Rc;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use std::any::type_name;
+#[cfg(feature = "alloc")]
+use std::convert::TryFrom;
+#[cfg(feature = "std")]
+use std::error::Error;
 use std::fmt;
-use std::mem::ManuallyDrop;
+use std::future::Future;
+use std::marker::PhantomPinned;
+#[cfg(feature = "alloc")]
+use std::mem::MaybeUninit;
+use std::mem::{self, ManuallyDrop};
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::ptr;
+use std::task::{Context, Poll};
 
 use RefMove;
 
@@ -19,7 +44,114 @@ pub trait Anchor<T, U: ?Sized> {
     /// ## Panics
     ///
     /// This method panics when called more than once.
-    fn borrow_move<'a>(&'a mut self) -> RefMove<'a, U>;
+    /// Use [`try_borrow_move`](#tymethod.try_borrow_move) to avoid it.
+    fn borrow_move<'a>(&'a mut self) -> RefMove<'a, U> {
+        match self.try_borrow_move() {
+            Ok(content) => content,
+            Err(AlreadyMoved) => panic!("double borrow_move from {}", type_name::<Self>()),
+        }
+    }
+    /// Turns a mutable reference to this anchor into a by-move reference
+    /// to its content, or returns an error if it has already been borrowed.
+    ///
+    /// ```rust
+    /// extern crate refmove;
+    /// use refmove::{Anchor, AnchorExt};
+    /// use refmove::anchor::AlreadyMoved;
+    ///
+    /// # fn main() {
+    /// let mut anchor = 42.anchor();
+    /// assert!(anchor.try_borrow_move().is_ok());
+    /// assert_eq!(anchor.try_borrow_move().err(), Some(AlreadyMoved));
+    /// # }
+    /// ```
+    fn try_borrow_move<'a>(&'a mut self) -> Result<RefMove<'a, U>, AlreadyMoved>;
+    /// Returns `true` if the content has not been borrowed.
+    fn is_anchored(&self) -> bool;
+    /// Unwraps the ownership, if the content has not been borrowed.
+    ///
+    /// ```rust
+    /// extern crate refmove;
+    /// use refmove::{Anchor, AnchorExt};
+    ///
+    /// # fn main() {
+    /// let mut anchor = 42.anchor();
+    /// assert!(anchor.is_anchored());
+    /// drop(anchor.borrow_move());
+    /// assert!(!anchor.is_anchored());
+    /// assert_eq!(anchor.into_inner(), None);
+    /// # }
+    /// ```
+    fn into_inner(self) -> Option<T>;
+}
+
+/// The error type returned when the content of an anchor has already been
+/// borrowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AlreadyMoved;
+
+impl fmt::Display for AlreadyMoved {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("the content of the anchor has already been moved")
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for AlreadyMoved {}
+
+/// By-move reference lent from an anchor, which the borrower may either
+/// consume or give back.
+///
+/// The borrower takes the ownership by [`MoveLoan::into_inner`] or
+/// [`MoveLoan::into_ref_move`]. If the loan is dropped instead, the content
+/// stays intact in the anchor, which is still anchored.
+///
+/// [`MoveLoan::into_inner`]: #method.into_inner
+/// [`MoveLoan::into_ref_move`]: #method.into_ref_move
+pub struct MoveLoan<'a, T: ?Sized + 'a> {
+    is_some: &'a mut bool,
+    content: &'a mut ManuallyDrop<T>,
+}
+
+impl<'a, T: ?Sized + 'a> MoveLoan<'a, T> {
+    /// Lends `content` of an anchor whose flag is `is_some`.
+    fn new(is_some: &'a mut bool, content: &'a mut ManuallyDrop<T>) -> Self {
+        MoveLoan { is_some, content }
+    }
+
+    /// Consumes the loan into a by-move reference, so that the content is
+    /// not returned to the anchor.
+    pub fn into_ref_move(this: Self) -> RefMove<'a, T> {
+        *this.is_some = false;
+        unsafe { RefMove::from_mut(this.content) }
+    }
+}
+
+impl<'a, T: 'a> MoveLoan<'a, T> {
+    /// Consumes the loan into its content, so that the content is not
+    /// returned to the anchor.
+    pub fn into_inner(this: Self) -> T {
+        Self::into_ref_move(this).into_inner()
+    }
+}
+
+impl<'a, T: ?Sized + 'a> Deref for MoveLoan<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.content
+    }
+}
+
+impl<'a, T: ?Sized + 'a> DerefMut for MoveLoan<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.content
+    }
+}
+
+impl<'a, T: fmt::Debug + ?Sized + 'a> fmt::Debug for MoveLoan<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("MoveLoan").field(&&**self).finish()
+    }
 }
 
 /// Anchor to obtain by-move reference to the stack.
@@ -39,13 +171,88 @@ impl<T> Anchor<T, T> for StackAnchor<T> {
         }
     }
 
-    fn borrow_move<'a>(&'a mut self) -> RefMove<'a, T> {
-        assert!(self.is_some, "double borrow_move from StackAnchor");
+    fn try_borrow_move<'a>(&'a mut self) -> Result<RefMove<'a, T>, AlreadyMoved> {
+        if !self.is_some {
+            return Err(AlreadyMoved);
+        }
         self.is_some = false;
-        unsafe { RefMove::from_mut(&mut self.content) }
+        Ok(unsafe { RefMove::from_mut(&mut self.content) })
+    }
+
+    fn is_anchored(&self) -> bool {
+        self.is_some
+    }
+
+    fn into_inner(self) -> Option<T> {
+        let mut this = ManuallyDrop::new(self);
+        if this.is_some {
+            Some(unsafe { ManuallyDrop::take(&mut this.content) })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> StackAnchor<T> {
+    /// Lends the content to the borrower, who may either consume it or give
+    /// it back by dropping the loan.
+    ///
+    /// Check `is_anchored` after the loan to learn which happened.
+    ///
+    /// ```rust
+    /// extern crate refmove;
+    /// use refmove::anchor::MoveLoan;
+    /// use refmove::{Anchor, AnchorExt};
+    ///
+    /// fn handle(request: MoveLoan<String>) {
+    ///     if request.starts_with("hoge") {
+    ///         drop(MoveLoan::into_inner(request));
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut anchor = "fuga".to_string().anchor();
+    /// handle(anchor.loan().unwrap());
+    /// // Declined; the request can be retried.
+    /// assert!(anchor.is_anchored());
+    /// assert_eq!(anchor.into_inner().unwrap(), "fuga");
+    /// # }
+    /// ```
+    pub fn loan<'a>(&'a mut self) -> Result<MoveLoan<'a, T>, AlreadyMoved> {
+        if !self.is_some {
+            return Err(AlreadyMoved);
+        }
+        Ok(MoveLoan::new(&mut self.is_some, &mut self.content))
+    }
+
+    /// Puts a new content into this anchor so that it can be borrowed again.
+    ///
+    /// The old content is dropped if it has not been borrowed.
+    ///
+    /// ```rust
+    /// extern crate refmove;
+    /// use refmove::{Anchor, AnchorExt};
+    ///
+    /// # fn main() {
+    /// let mut anchor = 0.anchor();
+    /// for i in 1..4 {
+    ///     assert_eq!(*anchor.refill(i).borrow_move(), i);
+    /// }
+    /// # }
+    /// ```
+    pub fn refill(&mut self, content: T) -> &mut Self {
+        if mem::replace(&mut self.is_some, false) {
+            unsafe {
+                ManuallyDrop::drop(&mut self.content);
+            }
+        }
+        self.content = ManuallyDrop::new(content);
+        self.is_some = true;
+        self
     }
 }
 
+#[cfg(feature = "nightly")]
 unsafe impl<#[may_dangle] T> Drop for StackAnchor<T> {
     fn drop(&mut self) {
         unsafe {
@@ -56,6 +263,17 @@ unsafe impl<#[may_dangle] T> Drop for StackAnchor<T> {
     }
 }
 
+#[cfg(not(feature = "nightly"))]
+impl<T> Drop for StackAnchor<T> {
+    fn drop(&mut self) {
+        unsafe {
+            if self.is_some {
+                ManuallyDrop::drop(&mut self.content);
+            }
+        }
+    }
+}
+
 impl<T: fmt::Debug> fmt::Debug for StackAnchor<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.is_some {
@@ -71,59 +289,456 @@ impl<T: fmt::Debug> fmt::Debug for StackAnchor<T> {
     }
 }
 
-/// Anchor to obtain by-move reference to the heap.
+/// Converts the pointer of a box with `f`, keeping its allocator.
 ///
-/// The structure is similar to `Box<Option<T>>` but `is_some` flag is
-/// out of `Box` so that we can reuse `Box<T>` pointer.
-#[cfg(feature = "std")]
-pub struct BoxAnchor<T: ?Sized> {
+/// ## Safety
+///
+/// `f` must return the given pointer itself, only changing its type to a
+/// layout-compatible one.
+#[cfg(all(feature = "alloc", feature = "nightly"))]
+unsafe fn cast_box<T, U, A, F>(content: Box<T, A>, f: F) -> Box<U, A>
+where
+    T: ?Sized,
+    U: ?Sized,
+    A: Allocator,
+    F: FnOnce(*mut T) -> *mut U,
+{
+    let (ptr, alloc) = Box::into_raw_with_allocator(content);
+    Box::from_raw_in(f(ptr), alloc)
+}
+
+/// Converts the pointer of a box with `f`.
+///
+/// ## Safety
+///
+/// `f` must return the given pointer itself, only changing its type to a
+/// layout-compatible one.
+#[cfg(all(feature = "alloc", not(feature = "nightly")))]
+unsafe fn cast_box<T, U, F>(content: Box<T>, f: F) -> Box<U>
+where
+    T: ?Sized,
+    U: ?Sized,
+    F: FnOnce(*mut T) -> *mut U,
+{
+    Box::from_raw(f(Box::into_raw(content)))
+}
+
+// Defines `BoxAnchor`, with an allocator parameter on nightly.
+#[cfg(feature = "alloc")]
+macro_rules! define_box_anchor {
+    ($($A:ident: $Allocator:ident = $Global:ident)?) => {
+        /// Anchor to obtain by-move reference to the heap.
+        ///
+        /// The structure is similar to `Box<Option<T>>` but `is_some` flag is
+        /// out of `Box` so that we can reuse `Box<T>` pointer.
+        /// With the `nightly` feature, it also accepts boxes with a custom
+        /// allocator `A`, to which the memory is returned when the anchor is
+        /// dropped.
+        pub struct BoxAnchor<T: ?Sized $(, $A: $Allocator = $Global)?> {
+            is_some: bool,
+            content: Box<ManuallyDrop<T> $(, $A)?>,
+        }
+
+        impl<T: ?Sized $(, $A: $Allocator)?> Anchor<Box<T $(, $A)?>, T>
+            for BoxAnchor<T $(, $A)?>
+        {
+            fn anchor_from(content: Box<T $(, $A)?>) -> Self {
+                Self {
+                    is_some: true,
+                    content: unsafe { cast_box(content, |p| p as *mut ManuallyDrop<T>) },
+                }
+            }
+
+            fn try_borrow_move<'a>(&'a mut self) -> Result<RefMove<'a, T>, AlreadyMoved> {
+                if !self.is_some {
+                    return Err(AlreadyMoved);
+                }
+                self.is_some = false;
+                Ok(unsafe { RefMove::from_mut(&mut self.content) })
+            }
+
+            fn is_anchored(&self) -> bool {
+                self.is_some
+            }
+
+            fn into_inner(self) -> Option<Box<T $(, $A)?>> {
+                let this = ManuallyDrop::new(self);
+                // Frees the allocation without dropping the content if borrowed.
+                let content = unsafe { ptr::read(&this.content) };
+                if this.is_some {
+                    Some(unsafe { cast_box(content, |p| p as *mut T) })
+                } else {
+                    None
+                }
+            }
+        }
+
+        impl<T: ?Sized $(, $A: $Allocator)?> BoxAnchor<T $(, $A)?> {
+            /// Same as [`StackAnchor::loan`](struct.StackAnchor.html#method.loan).
+            pub fn loan<'a>(&'a mut self) -> Result<MoveLoan<'a, T>, AlreadyMoved> {
+                if !self.is_some {
+                    return Err(AlreadyMoved);
+                }
+                Ok(MoveLoan::new(&mut self.is_some, &mut self.content))
+            }
+        }
+
+        impl<T $(, $A: $Allocator)?> BoxAnchor<T $(, $A)?> {
+            /// Puts a new content into the allocation of this anchor so that it
+            /// can be borrowed again.
+            ///
+            /// The old content is dropped if it has not been borrowed.
+            ///
+            /// ```rust
+            /// extern crate refmove;
+            /// use refmove::{Anchor, AnchorExt};
+            ///
+            /// # fn main() {
+            /// let mut anchor = Box::new(0).anchor_box();
+            /// for i in 1..4 {
+            ///     assert_eq!(*anchor.refill(i).borrow_move(), i);
+            /// }
+            /// // The allocation can be reused once more.
+            /// let b = anchor.into_uninit_box();
+            /// assert_eq!(*Box::write(b, 42), 42);
+            /// # }
+            /// ```
+            pub fn refill(&mut self, content: T) -> &mut Self {
+                if mem::replace(&mut self.is_some, false) {
+                    unsafe {
+                        ManuallyDrop::drop(&mut self.content);
+                    }
+                }
+                *self.content = ManuallyDrop::new(content);
+                self.is_some = true;
+                self
+            }
+
+            /// Turns this anchor into its allocation, without the content.
+            ///
+            /// The content is dropped if it has not been borrowed.
+            pub fn into_uninit_box(self) -> Box<MaybeUninit<T> $(, $A)?> {
+                let mut this = ManuallyDrop::new(self);
+                unsafe {
+                    if this.is_some {
+                        ManuallyDrop::drop(&mut this.content);
+                    }
+                    cast_box(ptr::read(&this.content), |p| p as *mut MaybeUninit<T>)
+                }
+            }
+        }
+
+        #[cfg(feature = "nightly")]
+        unsafe impl<#[may_dangle] T: ?Sized $(, $A: $Allocator)?> Drop
+            for BoxAnchor<T $(, $A)?>
+        {
+            fn drop(&mut self) {
+                unsafe {
+                    if self.is_some {
+                        ManuallyDrop::drop(&mut self.content);
+                    }
+                }
+            }
+        }
+
+        #[cfg(not(feature = "nightly"))]
+        impl<T: ?Sized $(, $A: $Allocator)?> Drop for BoxAnchor<T $(, $A)?> {
+            fn drop(&mut self) {
+                unsafe {
+                    if self.is_some {
+                        ManuallyDrop::drop(&mut self.content);
+                    }
+                }
+            }
+        }
+
+        impl<T: fmt::Debug + ?Sized $(, $A: $Allocator)?> fmt::Debug
+            for BoxAnchor<T $(, $A)?>
+        {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                if self.is_some {
+                    f.debug_struct("BoxAnchor")
+                        .field("is_some", &self.is_some)
+                        .field("content", &(&self.content as &T))
+                        .finish()
+                } else {
+                    f.debug_struct("BoxAnchor")
+                        .field("is_some", &self.is_some)
+                        .finish()
+                }
+            }
+        }
+    };
+}
+
+#[cfg(all(feature = "alloc", feature = "nightly"))]
+define_box_anchor!(A: Allocator = Global);
+#[cfg(all(feature = "alloc", not(feature = "nightly")))]
+define_box_anchor!();
+
+/// Anchor to obtain by-move reference to the buffer of a `Vec<T>`.
+///
+/// The elements are dropped by the `RefMove<[T]>` once borrowed, and the
+/// buffer is freed when the anchor is dropped.
+#[cfg(feature = "alloc")]
+pub struct VecAnchor<T> {
     is_some: bool,
-    content: Box<ManuallyDrop<T>>,
+    content: Vec<T>,
 }
 
-#[cfg(feature = "std")]
-impl<T: ?Sized> Anchor<Box<T>, T> for BoxAnchor<T> {
-    fn anchor_from(content: Box<T>) -> Self {
+#[cfg(feature = "alloc")]
+impl<T> Anchor<Vec<T>, [T]> for VecAnchor<T> {
+    fn anchor_from(content: Vec<T>) -> Self {
         Self {
             is_some: true,
-            content: unsafe { Box::from_raw(Box::into_raw(content) as *mut ManuallyDrop<T>) },
+            content,
         }
     }
 
-    fn borrow_move<'a>(&'a mut self) -> RefMove<'a, T> {
-        assert!(self.is_some, "double borrow_move from BoxAnchor");
+    fn try_borrow_move<'a>(&'a mut self) -> Result<RefMove<'a, [T]>, AlreadyMoved> {
+        if !self.is_some {
+            return Err(AlreadyMoved);
+        }
         self.is_some = false;
-        unsafe { RefMove::from_mut(&mut self.content) }
+        let len = self.content.len();
+        unsafe {
+            // The elements are owned by the RefMove from now on.
+            self.content.set_len(0);
+            Ok(RefMove::from_ptr(ptr::slice_from_raw_parts_mut(
+                self.content.as_mut_ptr(),
+                len,
+            )))
+        }
+    }
+
+    fn is_anchored(&self) -> bool {
+        self.is_some
+    }
+
+    fn into_inner(self) -> Option<Vec<T>> {
+        if self.is_some {
+            Some(self.content)
+        } else {
+            None
+        }
     }
 }
 
-#[cfg(feature = "std")]
-unsafe impl<#[may_dangle] T: ?Sized> Drop for BoxAnchor<T> {
-    fn drop(&mut self) {
+#[cfg(feature = "alloc")]
+impl<T: fmt::Debug> fmt::Debug for VecAnchor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_some {
+            f.debug_struct("VecAnchor")
+                .field("is_some", &self.is_some)
+                .field("content", &self.content)
+                .finish()
+        } else {
+            f.debug_struct("VecAnchor")
+                .field("is_some", &self.is_some)
+                .finish()
+        }
+    }
+}
+
+/// Anchor to obtain by-move reference to the buffer of a `String`.
+///
+/// The buffer is freed when the anchor is dropped.
+#[cfg(feature = "alloc")]
+pub struct StringAnchor {
+    is_some: bool,
+    content: String,
+}
+
+#[cfg(feature = "alloc")]
+impl Anchor<String, str> for StringAnchor {
+    fn anchor_from(content: String) -> Self {
+        Self {
+            is_some: true,
+            content,
+        }
+    }
+
+    fn try_borrow_move<'a>(&'a mut self) -> Result<RefMove<'a, str>, AlreadyMoved> {
+        if !self.is_some {
+            return Err(AlreadyMoved);
+        }
+        self.is_some = false;
+        let ptr: *mut str = self.content.as_mut_str();
         unsafe {
-            if self.is_some {
-                ManuallyDrop::drop(&mut self.content);
-            }
+            self.content.as_mut_vec().set_len(0);
+            Ok(RefMove::from_ptr(ptr))
+        }
+    }
+
+    fn is_anchored(&self) -> bool {
+        self.is_some
+    }
+
+    fn into_inner(self) -> Option<String> {
+        if self.is_some {
+            Some(self.content)
+        } else {
+            None
         }
     }
 }
 
-#[cfg(feature = "std")]
-impl<T: fmt::Debug + ?Sized> fmt::Debug for BoxAnchor<T> {
+#[cfg(feature = "alloc")]
+impl fmt::Debug for StringAnchor {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.is_some {
-            f.debug_struct("BoxAnchor")
+            f.debug_struct("StringAnchor")
                 .field("is_some", &self.is_some)
-                .field("content", &(&self.content as &T))
+                .field("content", &self.content)
                 .finish()
         } else {
-            f.debug_struct("BoxAnchor")
+            f.debug_struct("StringAnchor")
                 .field("is_some", &self.is_some)
                 .finish()
         }
     }
 }
 
+macro_rules! define_rc_anchor {
+    ($(#[$attr:meta])* $Anchor:ident, $Rc:ident) => {
+        $(#[$attr])*
+        #[cfg(feature = "alloc")]
+        pub struct $Anchor<T: ?Sized> {
+            is_some: bool,
+            content: $Rc<ManuallyDrop<T>>,
+        }
+
+        #[cfg(feature = "alloc")]
+        impl<T: ?Sized> TryFrom<$Rc<T>> for $Anchor<T> {
+            type Error = $Rc<T>;
+
+            /// Wraps the ownership by this anchor, or returns the pointer back
+            /// if it is not the only one to the allocation.
+            fn try_from(mut content: $Rc<T>) -> Result<Self, $Rc<T>> {
+                if $Rc::get_mut(&mut content).is_none() {
+                    return Err(content);
+                }
+                Ok(Self {
+                    is_some: true,
+                    content: unsafe {
+                        $Rc::from_raw($Rc::into_raw(content) as *const ManuallyDrop<T>)
+                    },
+                })
+            }
+        }
+
+        #[cfg(feature = "alloc")]
+        impl<T: ?Sized> Anchor<$Rc<T>, T> for $Anchor<T> {
+            /// Wraps the ownership by this anchor.
+            ///
+            /// ## Panics
+            ///
+            /// This method panics when the pointer is not the only one to
+            /// the allocation. Use `try_from` to get the pointer back instead.
+            fn anchor_from(content: $Rc<T>) -> Self {
+                match Self::try_from(content) {
+                    Ok(anchor) => anchor,
+                    Err(_) => panic!(concat!(
+                        "shared ",
+                        stringify!($Rc),
+                        " given to ",
+                        stringify!($Anchor)
+                    )),
+                }
+            }
+
+            fn try_borrow_move<'a>(&'a mut self) -> Result<RefMove<'a, T>, AlreadyMoved> {
+                if !self.is_some {
+                    return Err(AlreadyMoved);
+                }
+                self.is_some = false;
+                // Never fails: uniqueness is checked on construction and no
+                // pointer escapes.
+                let content = $Rc::get_mut(&mut self.content).unwrap();
+                Ok(unsafe { RefMove::from_mut(content) })
+            }
+
+            fn is_anchored(&self) -> bool {
+                self.is_some
+            }
+
+            fn into_inner(self) -> Option<$Rc<T>> {
+                let this = ManuallyDrop::new(self);
+                // Frees the allocation without dropping the content if borrowed.
+                let content = unsafe { ptr::read(&this.content) };
+                if this.is_some {
+                    Some(unsafe { $Rc::from_raw($Rc::into_raw(content) as *const T) })
+                } else {
+                    None
+                }
+            }
+        }
+
+        #[cfg(all(feature = "alloc", feature = "nightly"))]
+        unsafe impl<#[may_dangle] T: ?Sized> Drop for $Anchor<T> {
+            fn drop(&mut self) {
+                if self.is_some {
+                    if let Some(content) = $Rc::get_mut(&mut self.content) {
+                        unsafe {
+                            ManuallyDrop::drop(content);
+                        }
+                    }
+                }
+            }
+        }
+
+        #[cfg(all(feature = "alloc", not(feature = "nightly")))]
+        impl<T: ?Sized> Drop for $Anchor<T> {
+            fn drop(&mut self) {
+                if self.is_some {
+                    if let Some(content) = $Rc::get_mut(&mut self.content) {
+                        unsafe {
+                            ManuallyDrop::drop(content);
+                        }
+                    }
+                }
+            }
+        }
+
+        #[cfg(feature = "alloc")]
+        impl<T: fmt::Debug + ?Sized> fmt::Debug for $Anchor<T> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                if self.is_some {
+                    f.debug_struct(stringify!($Anchor))
+                        .field("is_some", &self.is_some)
+                        .field("content", &(&*self.content as &T))
+                        .finish()
+                } else {
+                    f.debug_struct(stringify!($Anchor))
+                        .field("is_some", &self.is_some)
+                        .finish()
+                }
+            }
+        }
+    };
+}
+
+define_rc_anchor!(
+    /// Anchor to obtain by-move reference into the allocation of a unique `Rc`.
+    ///
+    /// Unlike `Rc::try_unwrap`, it also works for unsized contents.
+    /// The allocation is freed when the anchor is dropped.
+    /// Create it with `RcAnchor::try_from(rc)`, which fails if there are
+    /// other `Rc` or `Weak` pointers to the allocation.
+    RcAnchor,
+    Rc
+);
+define_rc_anchor!(
+    /// Anchor to obtain by-move reference into the allocation of a unique `Arc`.
+    ///
+    /// Unlike `Arc::try_unwrap`, it also works for unsized contents.
+    /// The allocation is freed when the anchor is dropped.
+    /// Create it with `ArcAnchor::try_from(arc)`, which fails if there are
+    /// other `Arc` or `Weak` pointers to the allocation.
+    ArcAnchor,
+    Arc
+);
+
 /// Trivial anchor that just returns the given `RefMove`.
 #[derive(Debug)]
 pub struct IdentityAnchor<'a, T: ?Sized + 'a> {
@@ -137,9 +752,457 @@ impl<'a, T: ?Sized + 'a> Anchor<RefMove<'a, T>, T> for IdentityAnchor<'a, T> {
         }
     }
 
-    fn borrow_move<'b>(&'b mut self) -> RefMove<'b, T> {
+    fn try_borrow_move<'b>(&'b mut self) -> Result<RefMove<'b, T>, AlreadyMoved> {
+        self.content.take().ok_or(AlreadyMoved)
+    }
+
+    fn is_anchored(&self) -> bool {
+        self.content.is_some()
+    }
+
+    fn into_inner(self) -> Option<RefMove<'a, T>> {
         self.content
-            .take()
-            .expect("double borrow_move from IdentityAnchor")
+    }
+}
+
+/// Content of pinned anchors.
+///
+/// It notifies the anchor when dropped, so that the anchor can drop the
+/// content by itself if the by-move reference has been leaked.
+/// This is necessary to keep the drop guarantee of `Pin`.
+///
+/// The slot is never `Unpin`, so that it cannot be moved out of the anchor
+/// while it still points to the anchor. Use
+/// [`get_pin_mut`](#method.get_pin_mut) to access the pinned content.
+pub struct PinSlot<T: ?Sized> {
+    is_alive: *mut bool,
+    _pinned: PhantomPinned,
+    content: T,
+}
+
+impl<T: ?Sized> PinSlot<T> {
+    /// Projects the pinned slot to its pinned content.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+        unsafe { self.map_unchecked_mut(|this| &mut this.content) }
+    }
+}
+
+unsafe impl<T: Send + ?Sized> Send for PinSlot<T> {}
+unsafe impl<T: Sync + ?Sized> Sync for PinSlot<T> {}
+
+impl<T: ?Sized> Deref for PinSlot<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.content
+    }
+}
+
+impl<T: ?Sized> DerefMut for PinSlot<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.content
+    }
+}
+
+impl<F: Future + ?Sized> Future for PinSlot<F> {
+    type Output = F::Output;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<F::Output> {
+        self.get_pin_mut().poll(cx)
+    }
+}
+
+impl<T: ?Sized> Drop for PinSlot<T> {
+    fn drop(&mut self) {
+        if !self.is_alive.is_null() {
+            unsafe {
+                *self.is_alive = false;
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug + ?Sized> fmt::Debug for PinSlot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.content, f)
+    }
+}
+
+/// Anchor to obtain pinned by-move reference to the stack.
+///
+/// The anchor itself has to be pinned, for example by `core::pin::pin!`.
+/// Unlike `StackAnchor`, the content is dropped before the anchor even if
+/// the by-move reference is leaked.
+pub struct PinnedStackAnchor<T> {
+    is_some: bool,
+    is_alive: bool,
+    content: ManuallyDrop<PinSlot<T>>,
+    _pinned: PhantomPinned,
+}
+
+impl<T> PinnedStackAnchor<T> {
+    /// Wraps the ownership by this anchor.
+    pub fn new(content: T) -> Self {
+        Self {
+            is_some: true,
+            is_alive: true,
+            content: ManuallyDrop::new(PinSlot {
+                is_alive: ptr::null_mut(),
+                _pinned: PhantomPinned,
+                content,
+            }),
+            _pinned: PhantomPinned,
+        }
+    }
+
+    /// Turns a pinned mutable reference to this anchor into a pinned by-move
+    /// reference to its content.
+    ///
+    /// The slot cannot be moved out of the reference, even if `T: Unpin`:
+    ///
+    /// ```rust,compile_fail
+    /// extern crate refmove;
+    /// use std::pin::{pin, Pin};
+    /// use refmove::anchor::PinnedStackAnchor;
+    ///
+    /// # fn main() {
+    /// let anchor = pin!(PinnedStackAnchor::new(42));
+    /// let slot = Pin::into_inner(anchor.borrow_move()).into_inner();
+    /// # }
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// This method panics when called more than once.
+    pub fn borrow_move<'a>(self: Pin<&'a mut Self>) -> Pin<RefMove<'a, PinSlot<T>>> {
+        let this = unsafe { self.get_unchecked_mut() };
+        assert!(this.is_some, "double borrow_move from PinnedStackAnchor");
+        this.is_some = false;
+        this.content.is_alive = &mut this.is_alive;
+        unsafe { Pin::new_unchecked(RefMove::from_mut(&mut this.content)) }
+    }
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl<#[may_dangle] T> Drop for PinnedStackAnchor<T> {
+    fn drop(&mut self) {
+        if self.is_alive {
+            self.content.is_alive = ptr::null_mut();
+            unsafe {
+                ManuallyDrop::drop(&mut self.content);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T> Drop for PinnedStackAnchor<T> {
+    fn drop(&mut self) {
+        if self.is_alive {
+            self.content.is_alive = ptr::null_mut();
+            unsafe {
+                ManuallyDrop::drop(&mut self.content);
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PinnedStackAnchor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_some {
+            f.debug_struct("PinnedStackAnchor")
+                .field("is_some", &self.is_some)
+                .field("content", &self.content.content)
+                .finish()
+        } else {
+            f.debug_struct("PinnedStackAnchor")
+                .field("is_some", &self.is_some)
+                .finish()
+        }
+    }
+}
+
+/// Anchor to obtain pinned by-move reference to the heap.
+///
+/// Unlike `PinnedStackAnchor`, the anchor itself need not be pinned.
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct PinnedBoxAnchor<T> {
+    content: Pin<Box<PinnedStackAnchor<T>>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T> PinnedBoxAnchor<T> {
+    /// Moves the content to the heap and wraps the ownership by this anchor.
+    pub fn new(content: T) -> Self {
+        Self {
+            content: Box::pin(PinnedStackAnchor::new(content)),
+        }
+    }
+
+    /// Turns a mutable reference to this anchor into a pinned by-move
+    /// reference to its content.
+    ///
+    /// ## Panics
+    ///
+    /// This method panics when called more than once.
+    pub fn borrow_move<'a>(&'a mut self) -> Pin<RefMove<'a, PinSlot<T>>> {
+        self.content.as_mut().borrow_move()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(all(feature = "alloc", feature = "nightly"))]
+    use std::alloc::{AllocError, Layout};
+    use std::cell::Cell;
+    use std::future::{self, Future};
+    use std::mem;
+    use std::pin::{pin, Pin};
+    #[cfg(all(feature = "alloc", feature = "nightly"))]
+    use std::ptr::NonNull;
+    use std::task::{Context, Poll, Waker};
+
+    use super::*;
+    use test_util::DropCount;
+    use AnchorExt;
+
+    /// Counts deallocations made through it.
+    #[cfg(all(feature = "alloc", feature = "nightly"))]
+    struct CountingAlloc<'a>(&'a Cell<usize>);
+
+    #[cfg(all(feature = "alloc", feature = "nightly"))]
+    unsafe impl<'a> Allocator for CountingAlloc<'a> {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            Global.allocate(layout)
+        }
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.0.set(self.0.get() + 1);
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    #[cfg(all(feature = "alloc", feature = "nightly"))]
+    #[test]
+    fn test_box_anchor_allocator() {
+        let drops = Cell::new(0);
+        let deallocs = Cell::new(0);
+        let mut anchor = Box::new_in(DropCount(&drops), CountingAlloc(&deallocs)).anchor_box();
+        drop(anchor.borrow_move());
+        assert_eq!((drops.get(), deallocs.get()), (1, 0));
+        drop(anchor);
+        assert_eq!((drops.get(), deallocs.get()), (1, 1));
+    }
+
+    #[test]
+    fn test_pinned_stack_anchor() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let anchor = pin!(PinnedStackAnchor::new(future::ready(42)));
+        let x = anchor.borrow_move();
+        let mut x: Pin<RefMove<dyn Future<Output = i32>>> =
+            unsize_move!(x as dyn Future<Output = i32>);
+        assert_eq!(x.as_mut().poll(&mut cx), Poll::Ready(42));
+    }
+
+    #[test]
+    fn test_unpin_future() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut anchor = future::ready(42).anchor();
+        let mut x: RefMove<future::Ready<i32>> = anchor.borrow_move();
+        assert_eq!(Pin::new(&mut x).poll(&mut cx), Poll::Ready(42));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_vec_anchor() {
+        let count = Cell::new(0);
+        let v = vec![DropCount(&count), DropCount(&count), DropCount(&count)];
+        let mut anchor = v.anchor_box();
+        let x: RefMove<[DropCount]> = anchor.borrow_move();
+        let (first, rest) = RefMove::split_first(x).unwrap();
+        drop(rest);
+        assert_eq!(count.get(), 2);
+        drop(first);
+        assert_eq!(count.get(), 3);
+        drop(anchor);
+        assert_eq!(count.get(), 3);
+
+        let mut anchor = "hoge".to_string().anchor_box();
+        let x: RefMove<str> = anchor.borrow_move();
+        assert_eq!(x, "hoge");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_rc_anchor() {
+        let count = Cell::new(0);
+        let rc = Rc::new(DropCount(&count));
+        let rc2 = rc.clone();
+        let rc = RcAnchor::try_from(rc).unwrap_err();
+        drop(rc2);
+        let weak = Rc::downgrade(&rc);
+        let rc = RcAnchor::try_from(rc).unwrap_err();
+        drop(weak);
+        let mut anchor = RcAnchor::try_from(rc).ok().unwrap();
+        drop(anchor.borrow_move());
+        assert_eq!(count.get(), 1);
+        drop(anchor);
+        assert_eq!(count.get(), 1);
+
+        let arc: Arc<str> = Arc::from("hoge");
+        let mut anchor = ArcAnchor::try_from(arc).ok().unwrap();
+        assert_eq!(RefMove::into_box(anchor.borrow_move()), "hoge".into());
+    }
+
+    #[test]
+    fn test_try_borrow_move() {
+        let count = Cell::new(0);
+        let mut anchor = DropCount(&count).anchor();
+        assert!(anchor.is_anchored());
+        assert!(anchor.try_borrow_move().is_ok());
+        assert!(!anchor.is_anchored());
+        assert_eq!(anchor.try_borrow_move().err(), Some(AlreadyMoved));
+        assert!(anchor.into_inner().is_none());
+        assert_eq!(count.get(), 1);
+
+        let anchor = DropCount(&count).anchor();
+        let x = anchor.into_inner().unwrap();
+        assert_eq!(count.get(), 1);
+        drop(x);
+        assert_eq!(count.get(), 2);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_box_anchor_into_inner() {
+        let count = Cell::new(0);
+        let anchor = Box::new(DropCount(&count)).anchor_box();
+        let x = anchor.into_inner().unwrap();
+        assert_eq!(count.get(), 0);
+        let mut anchor = x.anchor_box();
+        drop(anchor.borrow_move());
+        assert_eq!(anchor.try_borrow_move().err(), Some(AlreadyMoved));
+        assert!(anchor.into_inner().is_none());
+        assert_eq!(count.get(), 1);
+
+        let rc: Rc<str> = Rc::from("hoge");
+        let anchor = RcAnchor::try_from(rc).ok().unwrap();
+        assert_eq!(anchor.into_inner().as_deref(), Some("hoge"));
+    }
+
+    #[test]
+    fn test_loan() {
+        let count = Cell::new(0);
+        let mut anchor = DropCount(&count).anchor();
+        {
+            let loan = anchor.loan().unwrap();
+            assert_eq!(loan.0.get(), 0);
+        }
+        assert!(anchor.is_anchored());
+        assert_eq!(count.get(), 0);
+        drop(MoveLoan::into_ref_move(anchor.loan().unwrap()));
+        assert!(!anchor.is_anchored());
+        assert_eq!(count.get(), 1);
+        assert_eq!(anchor.loan().err(), Some(AlreadyMoved));
+        drop(anchor);
+        assert_eq!(count.get(), 1);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_box_anchor_loan() {
+        let count = Cell::new(0);
+        let b: Box<[DropCount]> = vec![DropCount(&count), DropCount(&count)].into();
+        let mut anchor = b.anchor_box();
+        assert_eq!(anchor.loan().unwrap().len(), 2);
+        assert!(anchor.is_anchored());
+        assert_eq!(count.get(), 0);
+        drop(MoveLoan::into_ref_move(anchor.loan().unwrap()));
+        assert!(!anchor.is_anchored());
+        assert_eq!(count.get(), 2);
+    }
+
+    #[test]
+    fn test_refill() {
+        let count = Cell::new(0);
+        let mut anchor = DropCount(&count).anchor();
+        drop(anchor.borrow_move());
+        anchor.refill(DropCount(&count));
+        assert_eq!(count.get(), 1);
+        anchor.refill(DropCount(&count));
+        assert_eq!(count.get(), 2);
+        drop(anchor.borrow_move());
+        assert_eq!(count.get(), 3);
+        drop(anchor);
+        assert_eq!(count.get(), 3);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_box_anchor_refill() {
+        let count = Cell::new(0);
+        let mut anchor = Box::new(DropCount(&count)).anchor_box();
+        let x = anchor.borrow_move();
+        let addr = &*x as *const DropCount;
+        drop(x);
+        assert_eq!(count.get(), 1);
+        let x = anchor.refill(DropCount(&count)).borrow_move();
+        assert_eq!(&*x as *const DropCount, addr);
+        drop(x);
+        assert_eq!(count.get(), 2);
+        anchor.refill(DropCount(&count));
+        let b = anchor.into_uninit_box();
+        assert_eq!(count.get(), 3);
+        assert_eq!(b.as_ptr(), addr);
+    }
+
+    #[test]
+    fn test_pin_slot() {
+        let count = Cell::new(0);
+        let anchor = pin!(PinnedStackAnchor::new(DropCount(&count)));
+        let mut x = anchor.borrow_move();
+        let content: Pin<&mut DropCount> = x.as_mut().get_pin_mut();
+        assert_eq!(content.0.get(), 0);
+        drop(x);
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn test_pinned_anchor_leak() {
+        let count = Cell::new(0);
+        {
+            let mut anchor = Box::pin(PinnedStackAnchor::new(DropCount(&count)));
+            drop(anchor.as_mut().borrow_move());
+            assert_eq!(count.get(), 1);
+        }
+        assert_eq!(count.get(), 1);
+        {
+            let mut anchor = Box::pin(PinnedStackAnchor::new(DropCount(&count)));
+            mem::forget(anchor.as_mut().borrow_move());
+            assert_eq!(count.get(), 1);
+        }
+        assert_eq!(count.get(), 2);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_pinned_box_anchor() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut anchor = PinnedBoxAnchor::new(future::ready(42));
+        let x = anchor.borrow_move();
+        let mut x: Pin<RefMove<dyn Future<Output = i32>>> =
+            unsize_move!(x as dyn Future<Output = i32>);
+        assert_eq!(x.as_mut().poll(&mut cx), Poll::Ready(42));
+
+        let count = Cell::new(0);
+        let mut anchor = PinnedBoxAnchor::new(DropCount(&count));
+        drop(anchor.borrow_move());
+        assert_eq!(count.get(), 1);
+        drop(anchor);
+        assert_eq!(count.get(), 1);
+
+        let mut anchor = PinnedBoxAnchor::new(DropCount(&count));
+        mem::forget(anchor.borrow_move());
+        assert_eq!(count.get(), 1);
+        drop(anchor);
+        assert_eq!(count.get(), 2);
     }
 }
@@ -1,9 +1,22 @@
-use std::borrow::BorrowMut;
+#[cfg(all(feature = "alloc", feature = "nightly"))]
+use alloc::alloc::Allocator;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::rc::Rc;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use std::borrow::{Borrow, BorrowMut};
 use std::ops::Deref;
+use std::ptr;
 
-#[cfg(feature = "std")]
-use anchor::BoxAnchor;
 use anchor::{Anchor, IdentityAnchor, StackAnchor};
+#[cfg(feature = "alloc")]
+use anchor::{ArcAnchor, BoxAnchor, RcAnchor, StringAnchor, VecAnchor};
 use RefMove;
 
 /// Anchored by-move borrowing.
@@ -15,9 +28,13 @@ use RefMove;
 /// The point is that it's caller's responsibility to ensure the space is
 /// valid until `'a` expires.
 ///
+/// It only requires `Borrow`, not `BorrowMut`, so that shared pointers
+/// such as `Rc<T>` can implement it. Their anchors panic unless the pointer
+/// is the only one to the allocation.
+///
 /// [BorrowMove]: trait.BorrowMove.html
 /// [borrow_move]: ../anchor/trait.Anchor.html#tymethod.borrow_move
-pub trait BorrowInterior<Borrowed: ?Sized>: BorrowMut<Borrowed> + Sized {
+pub trait BorrowInterior<Borrowed: ?Sized>: Borrow<Borrowed> + Sized {
     /// The anchor type we use for this pair of borrowing.
     type Anchor: Anchor<Self, Borrowed>;
 }
@@ -30,23 +47,55 @@ impl<'a, T: ?Sized> BorrowInterior<T> for RefMove<'a, T> {
     type Anchor = IdentityAnchor<'a, T>;
 }
 
-#[cfg(feature = "std")]
+#[cfg(all(feature = "alloc", feature = "nightly"))]
+impl<T: ?Sized, A: Allocator> BorrowInterior<T> for Box<T, A> {
+    type Anchor = BoxAnchor<T, A>;
+}
+
+#[cfg(all(feature = "alloc", not(feature = "nightly")))]
 impl<T: ?Sized> BorrowInterior<T> for Box<T> {
     type Anchor = BoxAnchor<T>;
 }
 
+#[cfg(feature = "alloc")]
+impl<T> BorrowInterior<[T]> for Vec<T> {
+    type Anchor = VecAnchor<T>;
+}
+
+#[cfg(feature = "alloc")]
+impl BorrowInterior<str> for String {
+    type Anchor = StringAnchor;
+}
+
+#[cfg(feature = "alloc")]
+impl<T: ?Sized> BorrowInterior<T> for Rc<T> {
+    type Anchor = RcAnchor<T>;
+}
+
+#[cfg(feature = "alloc")]
+impl<T: ?Sized> BorrowInterior<T> for Arc<T> {
+    type Anchor = ArcAnchor<T>;
+}
+
 /// Provides `anchor` and `anchor_box` methods.
 pub trait AnchorExt: Sized {
     /// Wraps the value by `StackAnchor`.
-    /// With `#![feature(nll)]` enabled you can write `.anchor().borrow_move()`
-    /// to create `RefMove` pointing to the stack.
+    /// You can write `.anchor().borrow_move()` to create `RefMove` pointing
+    /// to the stack.
     fn anchor(self) -> StackAnchor<Self> {
         StackAnchor::anchor_from(self)
     }
 
-    /// Wraps the value by `BoxAnchor` or `IdentityAnchor`.
-    /// With `#![feature(nll)]` enabled you can write `.anchor_box().borrow_move()`
-    /// to create `RefMove` pointing to the heap.
+    /// Wraps the value by `BoxAnchor`, `VecAnchor`, `StringAnchor`,
+    /// `RcAnchor`, `ArcAnchor` or `IdentityAnchor`.
+    /// You can write `.anchor_box().borrow_move()` to create `RefMove`
+    /// pointing to the heap.
+    ///
+    /// ## Panics
+    ///
+    /// This method panics on an `Rc` or `Arc` that is not the only pointer
+    /// to its allocation. Use `RcAnchor::try_from` or `ArcAnchor::try_from`
+    /// to get the pointer back instead.
     fn anchor_box(self) -> Self::Anchor
     where
         Self: Deref,
@@ -58,6 +107,58 @@ pub trait AnchorExt: Sized {
 
 impl<T> AnchorExt for T {}
 
+/// Provides `take_move` method for `Option<T>` slots.
+pub trait TakeMove<T> {
+    /// Lends the content by move to `f` without moving it out of the slot,
+    /// and leaves `None` in the slot.
+    ///
+    /// Unlike `Option::take`, the `RefMove` given to `f` points into the
+    /// slot itself. The slot is set to `None` when `f` returns or panics.
+    ///
+    /// ```rust
+    /// extern crate refmove;
+    /// use refmove::TakeMove;
+    ///
+    /// # fn main() {
+    /// let mut slot = Some("hoge".to_string());
+    /// let len = slot.take_move(|x| x.map_or(0, |x| x.len()));
+    /// assert_eq!(len, 4);
+    /// assert_eq!(slot, None);
+    /// # }
+    /// ```
+    fn take_move<R, F>(&mut self, f: F) -> R
+    where
+        F: for<'a> FnOnce(Option<RefMove<'a, T>>) -> R;
+}
+
+impl<T> TakeMove<T> for Option<T> {
+    fn take_move<R, F>(&mut self, f: F) -> R
+    where
+        F: for<'a> FnOnce(Option<RefMove<'a, T>>) -> R,
+    {
+        let slot: *mut Option<T> = self;
+        let _release = ReleaseSlot(slot);
+        let content = unsafe { (*slot).as_mut().map(|x| RefMove::from_ptr(x)) };
+        f(content)
+    }
+}
+
+/// Sets the slot to `None` without dropping the content,
+/// which has been dropped by the `RefMove`.
+///
+/// It lives on `take_move`'s own stack frame rather than being an anchor
+/// handed to the user, because a leaked anchor would leave the dropped
+/// content in the slot.
+struct ReleaseSlot<T>(*mut Option<T>);
+
+impl<T> Drop for ReleaseSlot<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::write(self.0, None);
+        }
+    }
+}
+
 /// Unanchored by-move reborrowing.
 ///
 /// Unlike [`BorrowInterior`][BorrowInterior] the definition of `BorrowMove`
@@ -69,34 +170,94 @@ impl<T> AnchorExt for T {}
 /// Both stack-borrowing and heap-borrowing need some tweak to ensure validity.
 /// That's what [`BorrowInterior`][BorrowInterior] provides.
 ///
+/// `borrow_move` takes `this` rather than `self` because
+/// `self: RefMove<Self>` is not available on stable Rust:
+///
+/// ```rust
+/// extern crate refmove;
+/// use refmove::{Anchor, AnchorExt, BorrowMove, RefMove};
+/// # fn main() {
+/// let mut anchor = [1, 2, 3].anchor();
+/// let x: RefMove<[i32]> = BorrowMove::borrow_move(anchor.borrow_move());
+/// assert_eq!(x, &[1, 2, 3][..]);
+/// # }
+/// ```
+///
 /// [BorrowInterior]: trait.BorrowInterior.html
 pub trait BorrowMove<Borrowed: ?Sized>: BorrowMut<Borrowed> {
-    fn borrow_move<'a>(self: RefMove<'a, Self>) -> RefMove<'a, Borrowed>;
+    /// Reborrows the by-move reference as a by-move reference to `Borrowed`.
+    fn borrow_move<'a>(this: RefMove<'a, Self>) -> RefMove<'a, Borrowed>;
 }
 
 impl<T: ?Sized> BorrowMove<T> for T {
-    fn borrow_move<'a>(self: RefMove<'a, Self>) -> RefMove<'a, T> {
-        self
+    fn borrow_move<'a>(this: RefMove<'a, Self>) -> RefMove<'a, T> {
+        this
     }
 }
 
 impl<'a, T: ?Sized> BorrowMove<T> for RefMove<'a, T> {
-    fn borrow_move<'b>(self: RefMove<'b, Self>) -> RefMove<'b, T> {
-        self.into_inner()
+    fn borrow_move<'b>(this: RefMove<'b, Self>) -> RefMove<'b, T> {
+        this.into_inner()
     }
 }
 
-macro_rules! define_array_borrow {
-    ($($n:expr),*) => {
-        $(
-            impl<T> BorrowMove<[T]> for [T; $n] {
-                fn borrow_move<'a>(self: RefMove<'a, Self>) -> RefMove<'a, [T]> {
-                    self
-                }
-            }
-        )*
-    };
+impl<T, const N: usize> BorrowMove<[T]> for [T; N] {
+    fn borrow_move<'a>(this: RefMove<'a, Self>) -> RefMove<'a, [T]> {
+        unsize_move!(this as [T])
+    }
 }
 
-define_array_borrow!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
-define_array_borrow!(16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32);
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "alloc")]
+    use alloc::rc::Rc;
+    #[cfg(feature = "alloc")]
+    use alloc::sync::Arc;
+    use std::cell::Cell;
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::TakeMove;
+    #[cfg(feature = "alloc")]
+    use super::{Anchor, AnchorExt};
+    use test_util::DropCount;
+
+    #[test]
+    fn test_take_move() {
+        let count = Cell::new(0);
+        let mut slot = Some(DropCount(&count));
+        slot.take_move(|x| {
+            assert!(x.is_some());
+            assert_eq!(count.get(), 0);
+        });
+        assert!(slot.is_none());
+        assert_eq!(count.get(), 1);
+        slot.take_move(|x| assert!(x.is_none()));
+        assert_eq!(count.get(), 1);
+
+        let mut slot = Some(DropCount(&count));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            slot.take_move(|_| panic!("declined"));
+        }));
+        assert!(result.is_err());
+        assert!(slot.is_none());
+        assert_eq!(count.get(), 2);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_anchor_box_rc() {
+        let count = Cell::new(0);
+        let mut anchor = Rc::new(DropCount(&count)).anchor_box();
+        drop(anchor.borrow_move());
+        assert_eq!(count.get(), 1);
+
+        let mut anchor = Arc::new("hoge".to_string()).anchor_box();
+        assert_eq!(anchor.borrow_move().into_inner(), "hoge");
+
+        let rc = Rc::new(DropCount(&count));
+        let _other = rc.clone();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| rc.anchor_box()));
+        assert!(result.is_err());
+        assert_eq!(count.get(), 1);
+    }
+}
@@ -0,0 +1,189 @@
+//! Conversions from by-move references to owning pointers and containers.
+
+use alloc::alloc::{alloc, handle_alloc_error, Layout};
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use std::ptr;
+
+use RefMove;
+
+/// Replaces the address of a possibly wide pointer, keeping its metadata.
+unsafe fn set_addr<T: ?Sized>(mut ptr: *mut T, addr: *mut u8) -> *mut T {
+    *(&mut ptr as *mut *mut T as *mut *mut u8) = addr;
+    ptr
+}
+
+impl<'a, T: ?Sized + 'a> RefMove<'a, T> {
+    /// Moves the content into a new `Box`.
+    ///
+    /// Unlike `Box::new(RefMove::into_inner(this))`, it also works for
+    /// unsized contents such as trait objects, keeping the metadata.
+    ///
+    /// This is an associated function to avoid conflict with methods
+    /// of the content.
+    pub fn into_box(this: Self) -> Box<T> {
+        let layout = Layout::for_value::<T>(&this);
+        let src = RefMove::into_ptr(this);
+        unsafe {
+            let dst = if layout.size() == 0 {
+                ptr::without_provenance_mut(layout.align())
+            } else {
+                let dst = alloc(layout);
+                if dst.is_null() {
+                    handle_alloc_error(layout);
+                }
+                dst
+            };
+            ptr::copy_nonoverlapping(src as *const u8, dst, layout.size());
+            Box::from_raw(set_addr(src, dst))
+        }
+    }
+
+    /// Moves the content into a new `Rc`.
+    ///
+    /// It also works for unsized contents such as trait objects.
+    /// The content is first moved into a `Box`, which costs an extra
+    /// allocation and copy; sized contents, slices and `str` are moved into
+    /// a single allocation by `Rc::from` instead.
+    ///
+    /// This is an associated function to avoid conflict with methods
+    /// of the content.
+    pub fn into_rc(this: Self) -> Rc<T> {
+        Rc::from(RefMove::into_box(this))
+    }
+
+    /// Moves the content into a new `Arc`.
+    ///
+    /// It also works for unsized contents such as trait objects.
+    /// The content is first moved into a `Box`, which costs an extra
+    /// allocation and copy; sized contents, slices and `str` are moved into
+    /// a single allocation by `Arc::from` instead.
+    ///
+    /// This is an associated function to avoid conflict with methods
+    /// of the content.
+    pub fn into_arc(this: Self) -> Arc<T> {
+        Arc::from(RefMove::into_box(this))
+    }
+}
+
+macro_rules! define_rc_from {
+    ($Rc:ident) => {
+        impl<'a, T: 'a> From<RefMove<'a, T>> for $Rc<T> {
+            fn from(x: RefMove<'a, T>) -> Self {
+                $Rc::new(x.into_inner())
+            }
+        }
+
+        impl<'a, T: 'a> From<RefMove<'a, [T]>> for $Rc<[T]> {
+            fn from(slice: RefMove<'a, [T]>) -> Self {
+                let len = slice.len();
+                let mut rc = $Rc::<[T]>::new_uninit_slice(len);
+                let dst = $Rc::get_mut(&mut rc).unwrap().as_mut_ptr() as *mut T;
+                let src = RefMove::into_ptr(slice) as *const T;
+                unsafe {
+                    ptr::copy_nonoverlapping(src, dst, len);
+                    rc.assume_init()
+                }
+            }
+        }
+
+        impl<'a> From<RefMove<'a, str>> for $Rc<str> {
+            fn from(s: RefMove<'a, str>) -> Self {
+                let bytes: $Rc<[u8]> =
+                    unsafe { RefMove::from_ptr(RefMove::into_ptr(s) as *mut [u8]) }.into();
+                unsafe { $Rc::from_raw($Rc::into_raw(bytes) as *const str) }
+            }
+        }
+    };
+}
+
+define_rc_from!(Rc);
+define_rc_from!(Arc);
+
+impl<'a, T: 'a> From<RefMove<'a, [T]>> for Box<[T]> {
+    fn from(slice: RefMove<'a, [T]>) -> Self {
+        RefMove::into_box(slice)
+    }
+}
+
+impl<'a, T: 'a> From<RefMove<'a, [T]>> for Vec<T> {
+    fn from(slice: RefMove<'a, [T]>) -> Self {
+        RefMove::into_box(slice).into_vec()
+    }
+}
+
+impl<'a> From<RefMove<'a, str>> for Box<str> {
+    fn from(s: RefMove<'a, str>) -> Self {
+        RefMove::into_box(s)
+    }
+}
+
+impl<'a> From<RefMove<'a, str>> for String {
+    fn from(s: RefMove<'a, str>) -> Self {
+        RefMove::into_box(s).into_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::rc::Rc;
+    use alloc::sync::Arc;
+    use std::fmt::{Debug, Display};
+
+    use {Anchor, AnchorExt, BorrowMove, RefMove};
+
+    #[test]
+    fn test_into_box() {
+        let mut anchor = "hoge".to_string().anchor();
+        let x = unsize_move!((anchor.borrow_move()) as dyn Display);
+        let x: Box<dyn Display> = RefMove::into_box(x);
+        drop(anchor);
+        assert_eq!(x.to_string(), "hoge");
+
+        let mut anchor = ().anchor();
+        let x = unsize_move!((anchor.borrow_move()) as dyn Debug);
+        assert_eq!(format!("{:?}", RefMove::into_box(x)), "()");
+    }
+
+    #[test]
+    fn test_into_rc() {
+        let mut anchor = [1, 2, 3].anchor();
+        let x: RefMove<[i32]> = BorrowMove::borrow_move(anchor.borrow_move());
+        assert_eq!(*RefMove::into_rc(x), [1, 2, 3]);
+
+        let mut anchor = vec![4].anchor();
+        assert_eq!(*RefMove::into_arc(anchor.borrow_move()), [4]);
+    }
+
+    #[test]
+    fn test_rc_from() {
+        let mut anchor = "hoge".to_string().anchor();
+        let x: Rc<String> = anchor.borrow_move().into();
+        assert_eq!(*x, "hoge");
+
+        let mut anchor = vec!["fuga".to_string()].anchor_box();
+        let x: Arc<[String]> = anchor.borrow_move().into();
+        drop(anchor);
+        assert_eq!(*x, ["fuga"]);
+
+        let mut anchor = "piyo".to_string().anchor_box();
+        let x: Rc<str> = anchor.borrow_move().into();
+        assert_eq!(&*x, "piyo");
+    }
+
+    #[test]
+    fn test_into_vec() {
+        let mut anchor = ["hoge".to_string(), "fuga".to_string()].anchor();
+        let x: RefMove<[String]> = BorrowMove::borrow_move(anchor.borrow_move());
+        let v: Vec<String> = x.into();
+        drop(anchor);
+        assert_eq!(v, ["hoge", "fuga"]);
+
+        let mut anchor = Box::<str>::from("piyo").anchor_box();
+        let s: String = anchor.borrow_move().into();
+        assert_eq!(s, "piyo");
+    }
+}
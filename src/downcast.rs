@@ -2,13 +2,69 @@ use std::any::Any;
 
 use RefMove;
 
-impl<'a> RefMove<'a, dyn Any> {
-    /// Attempt to downcast the box to a concrete type.
-    pub fn downcast<T: Any>(self) -> Result<RefMove<'a, T>, Self> {
-        if self.is::<T>() {
+/// Trait objects that can be downcast by move.
+///
+/// It is implemented for every `'static` sized type and for `dyn Any`
+/// (with any combination of `Send` and `Sync`).
+/// Make it a supertrait of your trait to downcast your trait objects:
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate refmove;
+/// use refmove::{Anchor, AnchorExt, DowncastMove, RefMove};
+///
+/// trait Plugin: DowncastMove {}
+///
+/// struct Concrete(String);
+/// impl Plugin for Concrete {}
+///
+/// # fn main() {
+/// let mut anchor = Concrete("hoge".to_string()).anchor();
+/// let x: RefMove<dyn Plugin> = unsize_move!((anchor.borrow_move()) as dyn Plugin);
+/// let x: RefMove<Concrete> = x.downcast().ok().unwrap();
+/// assert_eq!(x.into_inner().0, "hoge");
+/// # }
+/// ```
+///
+/// ## Safety
+///
+/// `as_any_move` must return `self` itself.
+/// It is not named `as_any` to avoid ambiguity with the `as_any` method
+/// commonly defined on user traits.
+pub unsafe trait DowncastMove: Any {
+    /// Returns `self` as a `dyn Any`.
+    fn as_any_move(&self) -> &dyn Any;
+}
+
+unsafe impl<T: Any> DowncastMove for T {
+    fn as_any_move(&self) -> &dyn Any {
+        self
+    }
+}
+
+macro_rules! define_any_downcast {
+    ($($Any:ty),*) => {
+        $(
+            unsafe impl DowncastMove for $Any {
+                fn as_any_move(&self) -> &dyn Any {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+define_any_downcast!(dyn Any, dyn Any + Send, dyn Any + Send + Sync);
+
+impl<'a, T: DowncastMove + ?Sized> RefMove<'a, T> {
+    /// Attempt to downcast the reference to a concrete type.
+    ///
+    /// The original reference is returned back on failure.
+    pub fn downcast<U: Any>(self) -> Result<RefMove<'a, U>, Self> {
+        if T::as_any_move(&self).is::<U>() {
             unsafe {
-                let ptr: *mut Any = RefMove::into_ptr(self);
-                Ok(RefMove::from_ptr(ptr as *mut T))
+                let ptr: *mut T = RefMove::into_ptr(self);
+                Ok(RefMove::from_ptr(ptr as *mut U))
             }
         } else {
             Err(self)
@@ -16,16 +72,34 @@ impl<'a> RefMove<'a, dyn Any> {
     }
 }
 
-impl<'a> RefMove<'a, dyn Any + Send> {
-    /// Attempt to downcast the box to a concrete type.
-    pub fn downcast<T: Any>(self) -> Result<RefMove<'a, T>, Self> {
-        if self.is::<T>() {
-            unsafe {
-                let ptr: *mut Any = RefMove::into_ptr(self);
-                Ok(RefMove::from_ptr(ptr as *mut T))
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+
+    use {Anchor, AnchorExt, DowncastMove, RefMove};
+
+    #[test]
+    fn test_downcast() {
+        let mut anchor = "hoge".to_string().anchor();
+        let x = unsize_move!((anchor.borrow_move()) as dyn Any + Send + Sync);
+        let x = x.downcast::<i32>().unwrap_err();
+        let x: RefMove<String> = x.downcast().unwrap();
+        assert_eq!(x, "hoge");
+    }
+
+    #[test]
+    fn test_user_as_any() {
+        trait Plugin: DowncastMove {
+            fn as_any(&self) -> &dyn Any;
+        }
+        impl Plugin for String {
+            fn as_any(&self) -> &dyn Any {
+                self
             }
-        } else {
-            Err(self)
         }
+        let mut anchor = "hoge".to_string().anchor();
+        let x = unsize_move!((anchor.borrow_move()) as dyn Plugin);
+        assert!(x.as_any().is::<String>());
+        assert!(x.downcast::<String>().is_ok());
     }
 }
@@ -0,0 +1,197 @@
+//! Minimal local executor without allocation for the futures.
+//!
+//! Futures are passed as pinned by-move references, so that heterogeneous
+//! futures can be driven without `Box<dyn Future>`:
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate refmove;
+//! use std::future::{ready, Future};
+//! use std::pin::pin;
+//! use refmove::anchor::PinnedStackAnchor;
+//! use refmove::executor::{join_all, MoveFuture};
+//! use refmove::{Anchor, AnchorExt};
+//!
+//! # fn main() {
+//! let a = pin!(PinnedStackAnchor::new(ready(1)));
+//! let b = pin!(PinnedStackAnchor::new(ready(2)));
+//! let futures: [MoveFuture<i32>; 2] = [
+//!     unsize_move!((a.borrow_move()) as dyn Future<Output = i32>),
+//!     unsize_move!((b.borrow_move()) as dyn Future<Output = i32>),
+//! ];
+//! let mut anchor = futures.anchor();
+//! let mut outputs = [None, None];
+//! join_all(unsize_move!((anchor.borrow_move()) as [_]), &mut outputs);
+//! assert_eq!(outputs, [Some(1), Some(2)]);
+//! # }
+//! ```
+//!
+//! With the `std` feature, the executor parks the current thread while the
+//! futures are pending. Otherwise it keeps polling them with a no-op waker.
+
+use std::future::Future;
+#[cfg(not(feature = "std"))]
+use std::hint;
+use std::pin::{pin, Pin};
+use std::ptr;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::task::Wake;
+use std::task::{Context, Poll, Waker};
+#[cfg(feature = "std")]
+use std::thread::{self, Thread};
+
+use RefMove;
+
+/// Pinned by-move reference to a future.
+pub type MoveFuture<'a, T> = Pin<RefMove<'a, dyn Future<Output = T> + 'a>>;
+
+/// Runs a future to completion on the current thread.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    run(|cx| future.as_mut().poll(cx))
+}
+
+/// Runs the futures to completion on the current thread, concurrently.
+///
+/// The output of `futures[i]` is stored to `outputs[i]`.
+/// Each future is dropped in place as soon as it finishes.
+///
+/// ## Panics
+///
+/// Panics if the lengths differ or `outputs` contains `Some`.
+pub fn join_all<'a, T>(futures: RefMove<[MoveFuture<'a, T>]>, outputs: &mut [Option<T>]) {
+    assert_eq!(futures.len(), outputs.len(), "length mismatch");
+    assert!(
+        outputs.iter().all(Option::is_none),
+        "outputs must be initially empty"
+    );
+    let join = Join {
+        futures: RefMove::into_ptr(futures) as *mut MoveFuture<'a, T>,
+        outputs,
+    };
+    run(|cx| {
+        let mut pending = false;
+        for i in 0..join.outputs.len() {
+            if join.outputs[i].is_some() {
+                continue;
+            }
+            let future = unsafe { &mut *join.futures.add(i) };
+            match future.as_mut().poll(cx) {
+                Poll::Ready(output) => {
+                    join.outputs[i] = Some(output);
+                    unsafe {
+                        ptr::drop_in_place(future);
+                    }
+                }
+                Poll::Pending => pending = true,
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+}
+
+/// Owns the futures not finished yet, i.e. those whose output is `None`.
+struct Join<'a, 'b, T: 'a + 'b> {
+    futures: *mut MoveFuture<'a, T>,
+    outputs: &'b mut [Option<T>],
+}
+
+impl<'a, 'b, T: 'a + 'b> Drop for Join<'a, 'b, T> {
+    fn drop(&mut self) {
+        for (i, output) in self.outputs.iter().enumerate() {
+            if output.is_none() {
+                unsafe {
+                    ptr::drop_in_place(self.futures.add(i));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+struct ThreadWaker(Thread);
+
+#[cfg(feature = "std")]
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+#[cfg(feature = "std")]
+fn run<R, F: FnMut(&mut Context) -> Poll<R>>(mut f: F) -> R {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(ret) = f(&mut cx) {
+            return ret;
+        }
+        thread::park();
+    }
+}
+
+#[cfg(not(feature = "std"))]
+fn run<R, F: FnMut(&mut Context) -> Poll<R>>(mut f: F) -> R {
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(ret) = f(&mut cx) {
+            return ret;
+        }
+        hint::spin_loop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::{ready, Future};
+    use std::pin::{pin, Pin};
+    use std::task::{Context, Poll};
+
+    use super::{block_on, join_all, MoveFuture};
+    use anchor::PinnedStackAnchor;
+    use {Anchor, AnchorExt};
+
+    /// Returns `Pending` once before completing.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = &'static str;
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<&'static str> {
+            if self.0 {
+                Poll::Ready("yielded")
+            } else {
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_on() {
+        assert_eq!(block_on(YieldOnce(false)), "yielded");
+    }
+
+    #[test]
+    fn test_join_all() {
+        let a = pin!(PinnedStackAnchor::new(YieldOnce(false)));
+        let b = pin!(PinnedStackAnchor::new(ready("ready")));
+        let futures: [MoveFuture<&str>; 2] = [
+            unsize_move!((a.borrow_move()) as dyn Future<Output = &str>),
+            unsize_move!((b.borrow_move()) as dyn Future<Output = &str>),
+        ];
+        let mut anchor = futures.anchor();
+        let mut outputs = [None, None];
+        join_all(unsize_move!((anchor.borrow_move()) as [_]), &mut outputs);
+        assert_eq!(outputs, [Some("yielded"), Some("ready")]);
+    }
+}
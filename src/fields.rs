@@ -0,0 +1,195 @@
+//! Structural by-move borrowing.
+
+use RefMove;
+
+/// Types whose by-move references can be split into by-move references to
+/// their parts.
+///
+/// It is implemented for `Option<T>` and `Result<T, E>`, so that they can be
+/// matched on without moving the content out, and for tuples up to arity 12.
+///
+/// With the `derive` feature enabled, this can be derived for structs and
+/// enums without a `Drop` impl:
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate refmove;
+/// # #[cfg(feature = "derive")]
+/// # mod m {
+/// use refmove::RefMove;
+///
+/// #[derive(MoveFields)]
+/// pub struct Request {
+///     pub header: String,
+///     pub body: Vec<u8>,
+/// }
+///
+/// fn f(x: RefMove<Request>) {
+///     let fields: RequestFields = x.into_fields();
+///     let _: String = fields.header.into_inner();
+///     // `fields.body` is dropped in place here.
+/// }
+/// # }
+/// # fn main() {}
+/// ```
+///
+/// Types with a `Drop` impl are rejected:
+///
+/// ```rust,compile_fail
+/// #[macro_use]
+/// extern crate refmove;
+///
+/// #[derive(MoveFields)]
+/// struct Guard(String);
+///
+/// impl Drop for Guard {
+///     fn drop(&mut self) {}
+/// }
+/// # fn main() {}
+/// ```
+///
+/// even if every field is `Copy`:
+///
+/// ```rust,compile_fail
+/// #[macro_use]
+/// extern crate refmove;
+///
+/// #[derive(MoveFields)]
+/// struct Counter {
+///     id: u32,
+///     count: u64,
+/// }
+///
+/// impl Drop for Counter {
+///     fn drop(&mut self) {}
+/// }
+/// # fn main() {}
+/// ```
+///
+/// The same goes for enums:
+///
+/// ```rust,compile_fail
+/// #[macro_use]
+/// extern crate refmove;
+///
+/// #[derive(MoveFields)]
+/// enum Token {
+///     Number(u32),
+///     End,
+/// }
+///
+/// impl Drop for Token {
+///     fn drop(&mut self) {}
+/// }
+/// # fn main() {}
+/// ```
+pub trait MoveFields<'a>: Sized + 'a {
+    /// By-move references to the parts.
+    type Fields;
+
+    /// Splits the by-move reference. Prefer
+    /// [`RefMove::into_fields`](../struct.RefMove.html#method.into_fields).
+    fn move_fields(this: RefMove<'a, Self>) -> Self::Fields;
+}
+
+/// Implementation detail of `#[derive(MoveFields)]`.
+///
+/// The derive implements this for the deriving type, which conflicts with
+/// the blanket impl below if the type implements `Drop`.
+#[doc(hidden)]
+pub trait MustNotImplDrop {}
+
+#[allow(drop_bounds)]
+impl<T: Drop> MustNotImplDrop for T {}
+
+impl<'a, T: MoveFields<'a>> RefMove<'a, T> {
+    /// Splits the by-move reference into by-move references to the parts
+    /// of its content.
+    pub fn into_fields(self) -> T::Fields {
+        T::move_fields(self)
+    }
+}
+
+impl<'a, T: 'a> MoveFields<'a> for Option<T> {
+    type Fields = Option<RefMove<'a, T>>;
+
+    fn move_fields(this: RefMove<'a, Self>) -> Self::Fields {
+        let ptr = RefMove::into_ptr(this);
+        unsafe {
+            match *ptr {
+                Some(ref mut x) => Some(RefMove::from_ptr(x)),
+                None => None,
+            }
+        }
+    }
+}
+
+impl<'a, T: 'a, E: 'a> MoveFields<'a> for Result<T, E> {
+    type Fields = Result<RefMove<'a, T>, RefMove<'a, E>>;
+
+    fn move_fields(this: RefMove<'a, Self>) -> Self::Fields {
+        let ptr = RefMove::into_ptr(this);
+        unsafe {
+            match *ptr {
+                Ok(ref mut x) => Ok(RefMove::from_ptr(x)),
+                Err(ref mut e) => Err(RefMove::from_ptr(e)),
+            }
+        }
+    }
+}
+
+macro_rules! define_tuple_move_fields {
+    ($($T:ident $i:tt),*) => {
+        impl<'a, $($T: 'a),*> MoveFields<'a> for ($($T,)*) {
+            type Fields = ($(RefMove<'a, $T>,)*);
+
+            fn move_fields(this: RefMove<'a, Self>) -> Self::Fields {
+                let ptr = RefMove::into_ptr(this);
+                unsafe { ($(RefMove::from_ptr(&mut (*ptr).$i),)*) }
+            }
+        }
+    };
+}
+
+define_tuple_move_fields!(A 0);
+define_tuple_move_fields!(A 0, B 1);
+define_tuple_move_fields!(A 0, B 1, C 2);
+define_tuple_move_fields!(A 0, B 1, C 2, D 3);
+define_tuple_move_fields!(A 0, B 1, C 2, D 3, E 4);
+define_tuple_move_fields!(A 0, B 1, C 2, D 3, E 4, F 5);
+define_tuple_move_fields!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+define_tuple_move_fields!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+define_tuple_move_fields!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+define_tuple_move_fields!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+define_tuple_move_fields!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+define_tuple_move_fields!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+#[cfg(test)]
+mod tests {
+    use {Anchor, AnchorExt, RefMove};
+
+    #[test]
+    fn test_option() {
+        let mut anchor = Some("hoge".to_string()).anchor();
+        let x: Option<RefMove<String>> = anchor.borrow_move().into_fields();
+        assert_eq!(x.unwrap().into_inner(), "hoge");
+
+        let mut anchor = None::<String>.anchor();
+        assert!(anchor.borrow_move().into_fields().is_none());
+    }
+
+    #[test]
+    fn test_result() {
+        let mut anchor = Err::<i32, _>("fuga".to_string()).anchor();
+        let x: Result<RefMove<i32>, RefMove<String>> = anchor.borrow_move().into_fields();
+        assert_eq!(x.unwrap_err().into_inner(), "fuga");
+    }
+
+    #[test]
+    fn test_tuple() {
+        let mut anchor = ("hoge".to_string(), vec![1, 2]).anchor();
+        let (a, b): (RefMove<String>, RefMove<Vec<i32>>) = anchor.borrow_move().into_fields();
+        assert_eq!(b.into_inner(), [1, 2]);
+        assert_eq!(a.into_inner(), "hoge");
+    }
+}
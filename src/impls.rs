@@ -1,10 +1,23 @@
+#[cfg(all(feature = "nightly", feature = "alloc"))]
+use alloc::boxed::Box;
+#[cfg(all(feature = "nightly", feature = "alloc"))]
+use std::alloc::{AllocError, Allocator, Layout};
 use std::borrow::{Borrow, BorrowMut};
 use std::cmp;
 use std::fmt;
+use std::future::Future;
 use std::hash::{Hash, Hasher};
 #[cfg(feature = "std")]
 use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
-use std::iter::{FusedIterator, TrustedLen};
+use std::iter::FusedIterator;
+#[cfg(feature = "nightly")]
+use std::iter::TrustedLen;
+#[cfg(feature = "nightly")]
+use std::marker::Tuple;
+use std::pin::Pin;
+#[cfg(all(feature = "nightly", feature = "alloc"))]
+use std::ptr::NonNull;
+use std::task::{Context, Poll};
 
 use RefMove;
 
@@ -66,9 +79,6 @@ macro_rules! delegate_partial_ord {
             fn eq(&self, other: &$RefB) -> bool {
                 <$A as PartialEq<$B>>::eq(self, other)
             }
-            fn ne(&self, other: &$RefB) -> bool {
-                <$A as PartialEq<$B>>::ne(self, other)
-            }
         }
 
         impl<$a, $b, $A, $B> PartialOrd<$RefB> for $RefA
@@ -129,9 +139,49 @@ where
     }
 }
 
-impl<'a, A, F> FnOnce<A> for RefMove<'a, F>
+/// Allocator that never allocates or frees memory.
+///
+/// `Box<F, NoDealloc>` is used to move an unsized `F` out of a `RefMove`,
+/// because only `Box` can pass an unsized value by value. Such a box does
+/// not own a `NoDealloc` allocation, which breaks the contracts of
+/// `Box::from_raw_in` and `Allocator`; see `call_once` below for why this
+/// is fine for the current `impl FnOnce for Box<F, A>`.
+#[cfg(all(feature = "nightly", feature = "alloc"))]
+struct NoDealloc;
+
+#[cfg(all(feature = "nightly", feature = "alloc"))]
+unsafe impl Allocator for NoDealloc {
+    fn allocate(&self, _layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        Err(AllocError)
+    }
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+}
+
+#[cfg(all(feature = "nightly", feature = "alloc"))]
+impl<'a, A: Tuple, F> FnOnce<A> for RefMove<'a, F>
+where
+    F: FnOnce<A> + ?Sized,
+{
+    type Output = F::Output;
+    extern "rust-call" fn call_once(self, args: A) -> F::Output {
+        // SAFETY: This deliberately relies on how the standard library
+        // implements `FnOnce` for `Box<F, A>`: it moves `*self` out and
+        // then drops the emptied box, whose only effect is
+        // `A::deallocate`, a no-op for `NoDealloc`. The content is moved out
+        // exactly once and the memory is left to the anchor, just like
+        // `RefMove::into_inner` does. The box is never leaked, reallocated
+        // or otherwise handed to code that could observe the missing
+        // allocation. The `RefMove` grants exclusive access for `'a`, so
+        // the uniqueness of the box holds.
+        let boxed = unsafe { Box::from_raw_in(RefMove::into_ptr(self), NoDealloc) };
+        <Box<F, NoDealloc> as FnOnce<A>>::call_once(boxed, args)
+    }
+}
+
+#[cfg(all(feature = "nightly", not(feature = "alloc")))]
+impl<'a, A: Tuple, F> FnOnce<A> for RefMove<'a, F>
 where
-    F: FnMut<A> + ?Sized, // TODO
+    F: FnMut<A> + ?Sized,
 {
     type Output = F::Output;
     extern "rust-call" fn call_once(mut self, args: A) -> F::Output {
@@ -139,7 +189,8 @@ where
     }
 }
 
-impl<'a, A, F> FnMut<A> for RefMove<'a, F>
+#[cfg(feature = "nightly")]
+impl<'a, A: Tuple, F> FnMut<A> for RefMove<'a, F>
 where
     F: FnMut<A> + ?Sized,
 {
@@ -148,7 +199,8 @@ where
     }
 }
 
-impl<'a, A, F> Fn<A> for RefMove<'a, F>
+#[cfg(feature = "nightly")]
+impl<'a, A: Tuple, F> Fn<A> for RefMove<'a, F>
 where
     F: Fn<A> + ?Sized,
 {
@@ -157,6 +209,13 @@ where
     }
 }
 
+impl<'a, F: Future + Unpin + ?Sized> Future for RefMove<'a, F> {
+    type Output = F::Output;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<F::Output> {
+        F::poll(Pin::new(&mut **self), cx)
+    }
+}
+
 impl<'a, T: Hash + ?Sized> Hash for RefMove<'a, T> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         T::hash(self, state)
@@ -184,11 +243,13 @@ impl<'a, I: ExactSizeIterator + ?Sized> ExactSizeIterator for RefMove<'a, I> {
     fn len(&self) -> usize {
         I::len(self)
     }
+    #[cfg(feature = "nightly")]
     fn is_empty(&self) -> bool {
         I::is_empty(self)
     }
 }
 impl<'a, I: FusedIterator + ?Sized> FusedIterator for RefMove<'a, I> {}
+#[cfg(feature = "nightly")]
 unsafe impl<'a, I: TrustedLen + ?Sized> TrustedLen for RefMove<'a, I> {}
 
 #[cfg(feature = "std")]
@@ -218,10 +279,6 @@ impl<'a, R: Read + ?Sized> Read for RefMove<'a, R> {
         R::read(self, buf)
     }
     #[inline]
-    unsafe fn initializer(&self) -> io::Initializer {
-        R::initializer(self)
-    }
-    #[inline]
     fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
         R::read_to_end(self, buf)
     }
@@ -4,6 +4,11 @@
 //! It will enable you to use `self: RefMove<Self>` to pass your trait
 //! object by value, even without allocation.
 //!
+//! The crate builds on stable Rust. Unsizing coercions, `self: RefMove<Self>`
+//! and calling `RefMove<dyn FnOnce()>` need nightly Rust and the `nightly`
+//! feature. On stable Rust, use [`unsize_move!`](macro.unsize_move.html)
+//! to convert `RefMove<T>` into `RefMove<dyn Trait>`.
+//!
 //! See [#48055][#48055] for another approach to allow by-value trait objects.
 //!
 //! [#48055]: https://github.com/rust-lang/rust/issues/48055
@@ -13,24 +18,22 @@
 //! ### Borrowing
 //!
 //! ```rust
-//! #![feature(nll)]
 //! extern crate refmove;
 //! use refmove::{Anchor, AnchorExt, RefMove};
-//! # #[cfg(feature = "std")]
+//! # #[cfg(feature = "alloc")]
 //! # fn main() {
 //! // Borrowing from stack
 //! let _: RefMove<i32> = 42.anchor().borrow_move();
 //! // Borrowing from box
 //! let _: RefMove<i32> = Box::new(42).anchor_box().borrow_move();
 //! # }
-//! # #[cfg(not(feature = "std"))]
+//! # #[cfg(not(feature = "alloc"))]
 //! # fn main() {}
 //! ```
 //!
 //! ### Extracting
 //!
 //! ```rust
-//! #![feature(nll)]
 //! extern crate refmove;
 //! use refmove::{Anchor, AnchorExt, RefMove};
 //! # fn main() {
@@ -44,42 +47,78 @@
 //! }
 //! ```
 
-// We need it to achieve smooth borrowing.
-#![feature(nll)]
-// To implement Unpin
-#![feature(pin)]
 // To use #[may_dangle]
-#![feature(dropck_eyepatch)]
+#![cfg_attr(feature = "nightly", feature(dropck_eyepatch))]
 // To implement CoerceUnsized
-#![feature(unsize, coerce_unsized)]
+#![cfg_attr(feature = "nightly", feature(unsize, coerce_unsized))]
+// To coerce Pin<RefMove<T>> into Pin<RefMove<dyn Trait>>
+#![cfg_attr(feature = "nightly", feature(pin_coerce_unsized_trait))]
 // To use self: RefMove<Self>
-#![feature(arbitrary_self_types, dispatch_from_dyn)]
+#![cfg_attr(feature = "nightly", feature(arbitrary_self_types, dispatch_from_dyn))]
 // To implement FnOnce/FnMut/Fn
-#![feature(unboxed_closures, fn_traits)]
+#![cfg_attr(feature = "nightly", feature(unboxed_closures, fn_traits, tuple_trait))]
 // To implement ExactSizeIterator::is_empty
-#![feature(exact_size_is_empty)]
+#![cfg_attr(feature = "nightly", feature(exact_size_is_empty))]
 // To implement TrustedLen
-#![feature(trusted_len)]
-// To implement Read::initializer
-#![cfg_attr(feature = "std", feature(read_initializer))]
+#![cfg_attr(feature = "nightly", feature(trusted_len))]
+// To call RefMove<dyn FnOnce()> through Box<dyn FnOnce(), A>
+#![cfg_attr(all(feature = "nightly", feature = "alloc"), feature(allocator_api))]
 #![cfg_attr(not(any(feature = "std", test)), no_std)]
 #[cfg(not(any(feature = "std", test)))]
 use core as std;
 
-use std::marker::{PhantomData, Unpin, Unsize};
+#[cfg(feature = "nightly")]
+use std::marker::Unsize;
+use std::marker::{PhantomData, Unpin};
 use std::mem::{self, ManuallyDrop};
-use std::ops::{CoerceUnsized, Deref, DerefMut, DispatchFromDyn};
+#[cfg(feature = "nightly")]
+use std::ops::{CoerceUnsized, DispatchFromDyn};
+use std::ops::{Deref, DerefMut};
 #[cfg(feature = "std")]
 use std::panic::{RefUnwindSafe, UnwindSafe};
+#[cfg(feature = "nightly")]
+use std::pin::PinCoerceUnsized;
 use std::ptr::{self, drop_in_place, NonNull};
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "derive")]
+extern crate refmove_derive;
+
 pub use anchor::Anchor;
-pub use borrow::{AnchorExt, BorrowInterior, BorrowMove};
+pub use borrow::{AnchorExt, BorrowInterior, BorrowMove, TakeMove};
+pub use downcast::DowncastMove;
+pub use fields::MoveFields;
+#[doc(hidden)]
+pub use fields::MustNotImplDrop as __MustNotImplDrop;
+#[cfg(feature = "derive")]
+pub use refmove_derive::MoveFields;
+#[cfg(feature = "alloc")]
+pub use scope::with_move_box;
+pub use scope::{with_move, with_move2, with_move3, with_move4};
+#[doc(hidden)]
+pub use std::mem::ManuallyDrop as __ManuallyDrop;
+#[doc(hidden)]
+pub use unsize::UnsizeMove as __UnsizeMove;
+#[cfg(feature = "alloc")]
+pub use vec::DrainMove;
+
+#[macro_use]
+mod scope;
+#[macro_use]
+mod unsize;
 
 pub mod anchor;
 mod borrow;
+#[cfg(feature = "alloc")]
+mod boxed;
 mod downcast;
+pub mod executor;
+mod fields;
 mod impls;
+pub mod slice;
+#[cfg(feature = "alloc")]
+pub mod vec;
 
 /// Owned reference.
 ///
@@ -97,6 +136,14 @@ pub struct RefMove<'a, T: ?Sized + 'a> {
     _marker: PhantomData<(&'a (), T)>,
 }
 
+/// By-move callback, which can be called exactly once without allocation.
+///
+/// `MoveFn<'a, (A, B), R>` is `RefMove<'a, dyn FnOnce(A, B) -> R + 'a>`.
+/// Calling it consumes the captures of the closure by value.
+/// With the `alloc` feature disabled, only `FnMut` closures can be called.
+#[cfg(feature = "nightly")]
+pub type MoveFn<'a, Args, R> = RefMove<'a, dyn FnOnce<Args, Output = R> + 'a>;
+
 impl<'a, T: ?Sized + 'a> RefMove<'a, T> {
     /// Creates `RefMove` from its `ManuallyDrop` reference.
     ///
@@ -173,6 +220,7 @@ impl<'a, T: ?Sized + 'a> DerefMut for RefMove<'a, T> {
     }
 }
 
+#[cfg(feature = "nightly")]
 unsafe impl<'a, #[may_dangle] T: ?Sized + 'a> Drop for RefMove<'a, T> {
     fn drop(&mut self) {
         unsafe {
@@ -181,6 +229,16 @@ unsafe impl<'a, #[may_dangle] T: ?Sized + 'a> Drop for RefMove<'a, T> {
     }
 }
 
+#[cfg(not(feature = "nightly"))]
+impl<'a, T: ?Sized + 'a> Drop for RefMove<'a, T> {
+    fn drop(&mut self) {
+        unsafe {
+            drop_in_place(self.ptr.as_ptr());
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
 impl<'a, 'b, T, U> CoerceUnsized<RefMove<'a, U>> for RefMove<'b, T>
 where
     'b: 'a,
@@ -189,6 +247,11 @@ where
 {
 }
 
+// The address of the content never changes.
+#[cfg(feature = "nightly")]
+unsafe impl<'a, T: ?Sized + 'a> PinCoerceUnsized for RefMove<'a, T> {}
+
+#[cfg(feature = "nightly")]
 impl<'a, T, U> DispatchFromDyn<RefMove<'a, U>> for RefMove<'a, T>
 where
     T: Unsize<U> + ?Sized,
@@ -196,6 +259,21 @@ where
 {
 }
 
+#[cfg(test)]
+mod test_util {
+    use std::cell::Cell;
+
+    /// Counts how many times it is dropped.
+    #[derive(Debug)]
+    pub struct DropCount<'a>(pub &'a Cell<usize>);
+
+    impl<'a> Drop for DropCount<'a> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -203,30 +281,33 @@ mod tests {
     use Anchor;
     use AnchorExt;
 
+    #[cfg(feature = "nightly")]
     trait Foo {
         fn foo(self: RefMove<Self>);
     }
 
+    #[cfg(feature = "nightly")]
     impl Foo for String {
         fn foo(self: RefMove<Self>) {
             println!("{}", self);
         }
     }
 
-    #[cfg(feature = "std")]
+    #[cfg(all(feature = "alloc", feature = "nightly"))]
     #[test]
     fn test_object() {
-        let x: Box<Foo> = Box::new("hoge".to_string());
+        let x: Box<dyn Foo> = Box::new("hoge".to_string());
         x.anchor_box().borrow_move().foo();
     }
 
+    #[cfg(feature = "nightly")]
     #[test]
     fn test_object_nostd() {
         let x = "hoge".to_string();
-        (x.anchor().borrow_move() as RefMove<Foo>).foo();
+        (x.anchor().borrow_move() as RefMove<dyn Foo>).foo();
     }
 
-    #[cfg(feature = "std")]
+    #[cfg(feature = "alloc")]
     #[test]
     fn test_borrow_move() {
         fn f(x: RefMove<String>, e: &str) {
@@ -240,6 +321,20 @@ mod tests {
         );
     }
 
+    #[cfg(all(feature = "alloc", feature = "nightly"))]
+    #[test]
+    fn test_call_once() {
+        let s = "hoge".to_string();
+        let mut anchor = (move |suffix: &str| s + suffix).anchor();
+        let f: MoveFn<(&str,), String> = anchor.borrow_move();
+        assert_eq!(f("fuga"), "hogefuga");
+
+        let s = "piyo".to_string();
+        let mut anchor = (move || s).anchor();
+        let f: RefMove<dyn FnOnce() -> String + Send> = anchor.borrow_move();
+        assert_eq!(f(), "piyo");
+    }
+
     #[test]
     fn test_borrow_move_nostd() {
         fn f(x: RefMove<&mut i32>) {
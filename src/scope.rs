@@ -0,0 +1,247 @@
+//! Scope-based by-move borrowing, without runtime drop flags.
+
+#[cfg(feature = "alloc")]
+use std::ops::Deref;
+
+use RefMove;
+#[cfg(feature = "alloc")]
+use {Anchor, BorrowInterior};
+
+/// Binds a by-move reference to a value on the stack.
+///
+/// `let_move!(x = expr);` works like
+/// `let mut anchor = expr.anchor(); let x = anchor.borrow_move();`,
+/// except that the anchor is hidden from the user. Since the anchor can
+/// be borrowed only once, it needs no `is_some` flag, and there is no
+/// branch or panic path for a second `borrow_move`.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate refmove;
+/// use refmove::RefMove;
+///
+/// fn f(x: RefMove<String>) -> String {
+///     x.into_inner()
+/// }
+///
+/// # fn main() {
+/// let_move!(x = "hoge".to_string());
+/// assert_eq!(f(x), "hoge");
+///
+/// let_move!(mut y = vec![1, 2, 3]);
+/// y.push(4);
+/// assert_eq!(y.len(), 4);
+/// # }
+/// ```
+///
+/// The by-move reference cannot outlive the scope:
+///
+/// ```rust,compile_fail
+/// #[macro_use]
+/// extern crate refmove;
+/// use refmove::RefMove;
+///
+/// # fn main() {
+/// let x: RefMove<String> = {
+///     let_move!(x = "hoge".to_string());
+///     x
+/// };
+/// # }
+/// ```
+#[macro_export]
+macro_rules! let_move {
+    ($x:ident = $e:expr) => {
+        let mut anchor = $crate::__ManuallyDrop::new($e);
+        // The anchor is unnameable outside of the macro, so it is borrowed
+        // exactly once and its content is dropped only by the RefMove.
+        #[allow(unused_unsafe)]
+        let $x = unsafe { $crate::RefMove::from_mut(&mut anchor) };
+    };
+    (mut $x:ident = $e:expr) => {
+        let mut anchor = $crate::__ManuallyDrop::new($e);
+        #[allow(unused_unsafe)]
+        let mut $x = unsafe { $crate::RefMove::from_mut(&mut anchor) };
+    };
+}
+
+/// Lends the value by move to `f`.
+///
+/// Unlike `value.anchor().borrow_move()`, there is no anchor to name, and
+/// the `RefMove` cannot escape from `f`.
+///
+/// ```rust
+/// extern crate refmove;
+/// use refmove::{with_move, RefMove};
+///
+/// fn f(x: RefMove<String>) -> usize {
+///     x.len()
+/// }
+///
+/// # fn main() {
+/// assert_eq!(with_move("hoge".to_string(), f), 4);
+/// # }
+/// ```
+///
+/// ```rust,compile_fail
+/// extern crate refmove;
+/// use refmove::with_move;
+///
+/// # fn main() {
+/// let x = with_move("hoge".to_string(), |x| x);
+/// # }
+/// ```
+pub fn with_move<T, R, F>(value: T, f: F) -> R
+where
+    F: for<'a> FnOnce(RefMove<'a, T>) -> R,
+{
+    let_move!(x = value);
+    f(x)
+}
+
+/// Lends the content of the box by move to `f`, without moving it out of
+/// the box.
+///
+/// The allocation is freed after `f` returns.
+/// Besides `Box<T>`, it accepts every pointer that `anchor_box` accepts,
+/// such as `Vec<T>`, `String`, `Rc<T>`, and, with the `nightly` feature,
+/// boxes with a custom allocator.
+///
+/// ## Panics
+///
+/// Like `anchor_box`, this function panics on an `Rc` or `Arc` that is not
+/// the only pointer to its allocation.
+///
+/// ```rust
+/// extern crate refmove;
+/// use refmove::with_move_box;
+///
+/// # fn main() {
+/// let s: Box<str> = "hoge".into();
+/// assert_eq!(with_move_box(s, |x| x.len()), 4);
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+pub fn with_move_box<B, R, F>(boxed: B, f: F) -> R
+where
+    B: Deref + BorrowInterior<<B as Deref>::Target>,
+    F: for<'a> FnOnce(RefMove<'a, B::Target>) -> R,
+{
+    let mut anchor = B::Anchor::anchor_from(boxed);
+    f(anchor.borrow_move())
+}
+
+macro_rules! define_with_move_n {
+    ($(#[$attr:meta])* $name:ident, $($T:ident $value:ident),*) => {
+        $(#[$attr])*
+        pub fn $name<$($T,)* R, F>($($value: $T,)* f: F) -> R
+        where
+            F: for<'a> FnOnce($(RefMove<'a, $T>),*) -> R,
+        {
+            $(let_move!($value = $value);)*
+            f($($value),*)
+        }
+    };
+}
+
+define_with_move_n!(
+    /// Lends two values by move to `f` at once.
+    ///
+    /// ```rust
+    /// extern crate refmove;
+    /// use refmove::with_move2;
+    ///
+    /// # fn main() {
+    /// let s = with_move2("hoge".to_string(), "fuga".to_string(), |x, y| {
+    ///     x.into_inner() + &y
+    /// });
+    /// assert_eq!(s, "hogefuga");
+    /// # }
+    /// ```
+    with_move2,
+    A a,
+    B b
+);
+define_with_move_n!(
+    /// Lends three values by move to `f` at once.
+    with_move3,
+    A a,
+    B b,
+    C c
+);
+define_with_move_n!(
+    /// Lends four values by move to `f` at once.
+    with_move4,
+    A a,
+    B b,
+    C c,
+    D d
+);
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::mem;
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::*;
+    use test_util::DropCount;
+
+    #[test]
+    fn test_let_move() {
+        let count = Cell::new(0);
+        {
+            let_move!(x = DropCount(&count));
+            let _ = &x;
+            assert_eq!(count.get(), 0);
+        }
+        assert_eq!(count.get(), 1);
+        {
+            let_move!(x = DropCount(&count));
+            drop(x);
+            assert_eq!(count.get(), 2);
+        }
+        assert_eq!(count.get(), 2);
+        {
+            let_move!(x = DropCount(&count));
+            mem::forget(x);
+        }
+        assert_eq!(count.get(), 2);
+    }
+
+    #[test]
+    fn test_with_move() {
+        let count = Cell::new(0);
+        with_move(DropCount(&count), |_| assert_eq!(count.get(), 0));
+        assert_eq!(count.get(), 1);
+        with_move(DropCount(&count), |x| mem::forget(x));
+        assert_eq!(count.get(), 1);
+
+        let s = String::from("hoge");
+        let n = with_move3(&s, DropCount(&count), 42, |x, y, z| {
+            drop(y);
+            x.len() + *z
+        });
+        assert_eq!(n, 46);
+        assert_eq!(count.get(), 2);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            with_move4(1, DropCount(&count), 3, DropCount(&count), |_, _, _, _| {
+                panic!("declined")
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(count.get(), 4);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_with_move_box() {
+        let count = Cell::new(0);
+        with_move_box(Box::new(DropCount(&count)), |_| assert_eq!(count.get(), 0));
+        assert_eq!(count.get(), 1);
+        let v: Box<[DropCount]> = vec![DropCount(&count), DropCount(&count)].into();
+        let n = with_move_box(v, |x| x.len());
+        assert_eq!(n, 2);
+        assert_eq!(count.get(), 3);
+    }
+}
@@ -0,0 +1,414 @@
+//! By-move references to slices and arrays.
+
+use std::array;
+use std::fmt;
+use std::iter::FusedIterator;
+#[cfg(feature = "nightly")]
+use std::iter::TrustedLen;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::{self, NonNull};
+use std::slice;
+
+use RefMove;
+
+impl<'a, T: 'a> RefMove<'a, [T]> {
+    unsafe fn from_raw_parts(ptr: *mut T, len: usize) -> Self {
+        RefMove::from_ptr(ptr::slice_from_raw_parts_mut(ptr, len))
+    }
+
+    /// Divides one by-move slice into two at an index.
+    ///
+    /// The first will contain all indices from `[0, mid)` and the second
+    /// will contain all indices from `[mid, len)`.
+    ///
+    /// This is an associated function to avoid conflict with
+    /// `<[T]>::split_at`.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `mid > len`.
+    pub fn split_at(this: Self, mid: usize) -> (Self, Self) {
+        let len = this.len();
+        assert!(mid <= len, "mid > len");
+        let ptr = RefMove::into_ptr(this) as *mut T;
+        unsafe {
+            (
+                Self::from_raw_parts(ptr, mid),
+                Self::from_raw_parts(ptr.add(mid), len - mid),
+            )
+        }
+    }
+
+    /// Splits off the first element, or returns `None` if it is empty.
+    ///
+    /// This is an associated function to avoid conflict with
+    /// `<[T]>::split_first`.
+    pub fn split_first(this: Self) -> Option<(RefMove<'a, T>, Self)> {
+        let len = this.len();
+        if len == 0 {
+            return None;
+        }
+        let ptr = RefMove::into_ptr(this) as *mut T;
+        unsafe {
+            Some((
+                RefMove::from_ptr(ptr),
+                Self::from_raw_parts(ptr.add(1), len - 1),
+            ))
+        }
+    }
+
+    /// Splits off the last element, or returns `None` if it is empty.
+    ///
+    /// This is an associated function to avoid conflict with
+    /// `<[T]>::split_last`.
+    pub fn split_last(this: Self) -> Option<(RefMove<'a, T>, Self)> {
+        let len = this.len();
+        if len == 0 {
+            return None;
+        }
+        let ptr = RefMove::into_ptr(this) as *mut T;
+        unsafe {
+            Some((
+                RefMove::from_ptr(ptr.add(len - 1)),
+                Self::from_raw_parts(ptr, len - 1),
+            ))
+        }
+    }
+
+    /// Converts the by-move slice into a by-move array.
+    ///
+    /// Returns the original slice back if its length is not `N`.
+    pub fn try_into_array<const N: usize>(self) -> Result<RefMove<'a, [T; N]>, Self> {
+        if self.len() == N {
+            let ptr = RefMove::into_ptr(self) as *mut [T; N];
+            Ok(unsafe { RefMove::from_ptr(ptr) })
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Returns an iterator over `chunk_size` elements of the slice at a time,
+    /// each as a by-move slice.
+    ///
+    /// If `chunk_size` does not divide the length of the slice, the last
+    /// up to `chunk_size - 1` elements are left in the remainder, which can be
+    /// retrieved with [`ChunksExact::into_remainder`].
+    ///
+    /// This is an associated function to avoid conflict with
+    /// `<[T]>::chunks_exact`.
+    ///
+    /// [`ChunksExact::into_remainder`]: struct.ChunksExact.html#method.into_remainder
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn chunks_exact(this: Self, chunk_size: usize) -> ChunksExact<'a, T> {
+        assert!(chunk_size != 0, "chunk_size must not be zero");
+        let len = this.len();
+        let (v, rem) = Self::split_at(this, len - len % chunk_size);
+        ChunksExact { v, rem, chunk_size }
+    }
+
+    /// Creates an iterator that moves each element out of the slice.
+    ///
+    /// Elements not yet yielded are dropped together with the iterator.
+    ///
+    /// `RefMove<'a, [T]>` cannot implement `IntoIterator` itself because it
+    /// would overlap with the `Iterator` forwarding impl for `RefMove<'a, I>`.
+    /// Use `for x in slice.into_iter()` instead.
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter(self) -> IntoIter<'a, T> {
+        let len = self.len();
+        let ptr = RefMove::into_ptr(self) as *mut T;
+        IntoIter {
+            ptr: unsafe { NonNull::new_unchecked(ptr) },
+            start: 0,
+            end: len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// An iterator that moves out of a by-move reference to a slice.
+///
+/// This `struct` is created by the `into_iter` method on
+/// `RefMove<'a, [T]>`.
+/// Elements not yet yielded are dropped together with the iterator.
+pub struct IntoIter<'a, T: 'a> {
+    ptr: NonNull<T>,
+    start: usize,
+    end: usize,
+    _marker: PhantomData<(&'a (), T)>,
+}
+
+impl<'a, T: 'a> IntoIter<'a, T> {
+    /// Returns the remaining items of this iterator as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr().add(self.start), self.end - self.start) }
+    }
+
+    /// Returns the remaining items of this iterator as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe {
+            slice::from_raw_parts_mut(self.ptr.as_ptr().add(self.start), self.end - self.start)
+        }
+    }
+
+    /// Turns the remaining items of this iterator back into
+    /// a by-move reference.
+    pub fn into_ref_move(self) -> RefMove<'a, [T]> {
+        let ptr = unsafe {
+            ptr::slice_from_raw_parts_mut(self.ptr.as_ptr().add(self.start), self.end - self.start)
+        };
+        mem::forget(self);
+        unsafe { RefMove::from_ptr(ptr) }
+    }
+}
+
+unsafe impl<'a, T: Send + 'a> Send for IntoIter<'a, T> {}
+unsafe impl<'a, T: Sync + 'a> Sync for IntoIter<'a, T> {}
+
+impl<'a, T: 'a> Iterator for IntoIter<'a, T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            None
+        } else {
+            let ret = unsafe { ptr::read(self.ptr.as_ptr().add(self.start)) };
+            self.start += 1;
+            Some(ret)
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+    fn count(self) -> usize {
+        self.len()
+    }
+}
+
+impl<'a, T: 'a> DoubleEndedIterator for IntoIter<'a, T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            None
+        } else {
+            self.end -= 1;
+            Some(unsafe { ptr::read(self.ptr.as_ptr().add(self.end)) })
+        }
+    }
+}
+
+impl<'a, T: 'a> ExactSizeIterator for IntoIter<'a, T> {
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+    #[cfg(feature = "nightly")]
+    fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl<'a, T: 'a> FusedIterator for IntoIter<'a, T> {}
+#[cfg(feature = "nightly")]
+unsafe impl<'a, T: 'a> TrustedLen for IntoIter<'a, T> {}
+
+#[cfg(feature = "nightly")]
+unsafe impl<'a, #[may_dangle] T: 'a> Drop for IntoIter<'a, T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.as_mut_slice());
+        }
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<'a, T: 'a> Drop for IntoIter<'a, T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.as_mut_slice());
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug + 'a> fmt::Debug for IntoIter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.as_slice()).finish()
+    }
+}
+
+impl<'a, T: 'a, const N: usize> From<RefMove<'a, [T; N]>> for [RefMove<'a, T>; N] {
+    /// Splits a by-move array into by-move references to each element.
+    fn from(array: RefMove<'a, [T; N]>) -> Self {
+        let ptr = RefMove::into_ptr(array) as *mut T;
+        array::from_fn(|i| unsafe { RefMove::from_ptr(ptr.add(i)) })
+    }
+}
+
+fn empty<'a, T: 'a>() -> RefMove<'a, [T]> {
+    unsafe { RefMove::from_raw_parts(NonNull::dangling().as_ptr(), 0) }
+}
+
+/// An iterator over a by-move slice in non-overlapping chunks,
+/// starting at the beginning of the slice.
+///
+/// This `struct` is created by [`RefMove::chunks_exact`].
+/// Chunks not yet yielded, as well as the remainder, are dropped together
+/// with the iterator.
+///
+/// [`RefMove::chunks_exact`]: ../struct.RefMove.html#method.chunks_exact
+#[derive(Debug)]
+pub struct ChunksExact<'a, T: 'a> {
+    v: RefMove<'a, [T]>,
+    rem: RefMove<'a, [T]>,
+    chunk_size: usize,
+}
+
+impl<'a, T: 'a> ChunksExact<'a, T> {
+    /// Returns the remainder of the original slice that is not going to be
+    /// returned by the iterator, dropping the chunks not yet yielded.
+    pub fn into_remainder(self) -> RefMove<'a, [T]> {
+        self.rem
+    }
+}
+
+impl<'a, T: 'a> Iterator for ChunksExact<'a, T> {
+    type Item = RefMove<'a, [T]>;
+    fn next(&mut self) -> Option<RefMove<'a, [T]>> {
+        if self.v.len() < self.chunk_size {
+            None
+        } else {
+            let v = mem::replace(&mut self.v, empty());
+            let (head, tail) = RefMove::split_at(v, self.chunk_size);
+            self.v = tail;
+            Some(head)
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.v.len() / self.chunk_size;
+        (len, Some(len))
+    }
+    fn count(self) -> usize {
+        self.len()
+    }
+}
+
+impl<'a, T: 'a> DoubleEndedIterator for ChunksExact<'a, T> {
+    fn next_back(&mut self) -> Option<RefMove<'a, [T]>> {
+        if self.v.len() < self.chunk_size {
+            None
+        } else {
+            let v = mem::replace(&mut self.v, empty());
+            let mid = v.len() - self.chunk_size;
+            let (head, tail) = RefMove::split_at(v, mid);
+            self.v = head;
+            Some(tail)
+        }
+    }
+}
+
+impl<'a, T: 'a> ExactSizeIterator for ChunksExact<'a, T> {
+    #[cfg(feature = "nightly")]
+    fn is_empty(&self) -> bool {
+        self.v.len() < self.chunk_size
+    }
+}
+
+impl<'a, T: 'a> FusedIterator for ChunksExact<'a, T> {}
+#[cfg(feature = "nightly")]
+unsafe impl<'a, T: 'a> TrustedLen for ChunksExact<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use test_util::DropCount;
+    use {Anchor, AnchorExt, BorrowMove, RefMove};
+
+    #[test]
+    fn test_into_iter() {
+        let mut anchor = ["a".to_string(), "b".to_string(), "c".to_string()].anchor();
+        let x: RefMove<[String]> = BorrowMove::borrow_move(anchor.borrow_move());
+        let mut iter = x.into_iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next(), Some("a".to_string()));
+        assert_eq!(iter.next_back(), Some("c".to_string()));
+        assert_eq!(iter.as_slice(), &["b".to_string()]);
+        assert_eq!(iter.next(), Some("b".to_string()));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn test_into_iter_drop_rest() {
+        let count = Cell::new(0);
+        let mut anchor = [
+            DropCount(&count),
+            DropCount(&count),
+            DropCount(&count),
+            DropCount(&count),
+        ]
+        .anchor();
+        let x: RefMove<[DropCount]> = BorrowMove::borrow_move(anchor.borrow_move());
+        let mut iter = x.into_iter();
+        let first = iter.next().unwrap();
+        assert_eq!(count.get(), 0);
+        drop(iter);
+        assert_eq!(count.get(), 3);
+        drop(first);
+        assert_eq!(count.get(), 4);
+        drop(anchor);
+        assert_eq!(count.get(), 4);
+    }
+
+    #[test]
+    fn test_split() {
+        let count = Cell::new(0);
+        let mut anchor = [
+            DropCount(&count),
+            DropCount(&count),
+            DropCount(&count),
+            DropCount(&count),
+            DropCount(&count),
+        ]
+        .anchor();
+        let x: RefMove<[DropCount]> = BorrowMove::borrow_move(anchor.borrow_move());
+        let (first, rest) = RefMove::split_first(x).unwrap();
+        let (last, rest) = RefMove::split_last(rest).unwrap();
+        let (left, right) = RefMove::split_at(rest, 1);
+        assert_eq!((left.len(), right.len()), (1, 2));
+        drop(right);
+        assert_eq!(count.get(), 2);
+        drop((first, last));
+        assert_eq!(count.get(), 4);
+        drop(left);
+        assert_eq!(count.get(), 5);
+    }
+
+    #[test]
+    fn test_chunks_exact() {
+        let mut anchor = [1, 2, 3, 4, 5, 6, 7].anchor();
+        let x: RefMove<[i32]> = BorrowMove::borrow_move(anchor.borrow_move());
+        let mut chunks = RefMove::chunks_exact(x, 3);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks.next_back().unwrap(), &[4, 5, 6][..]);
+        assert_eq!(chunks.next().unwrap(), &[1, 2, 3][..]);
+        assert!(chunks.next().is_none());
+        assert_eq!(chunks.into_remainder(), &[7][..]);
+    }
+
+    #[test]
+    fn test_array() {
+        let count = Cell::new(0);
+        let mut anchor = [DropCount(&count), DropCount(&count), DropCount(&count)].anchor();
+        let x: RefMove<[DropCount]> = BorrowMove::borrow_move(anchor.borrow_move());
+        let x = x.try_into_array::<2>().unwrap_err();
+        let x: RefMove<[DropCount; 3]> = x.try_into_array().ok().unwrap();
+        let [a, b, c]: [RefMove<DropCount>; 3] = x.into();
+        drop(b);
+        assert_eq!(count.get(), 1);
+        drop((a, c));
+        assert_eq!(count.get(), 3);
+    }
+}
@@ -0,0 +1,122 @@
+//! Explicit unsizing, usable without `CoerceUnsized`.
+
+use std::pin::Pin;
+
+use RefMove;
+
+/// Converts a by-move reference into a by-move reference to an unsized type.
+///
+/// `unsize_move!(x as U)` performs the same conversion as the coercion
+/// `x as RefMove<U>`, which needs the `nightly` feature.
+/// `x` is either `RefMove<T>` or `Pin<RefMove<T>>`, and has to be an
+/// identifier or be parenthesized.
+/// `T` can be converted to `U` if `T` is an array and `U` is a slice,
+/// or if `T` implements `U`, or if `T` is a trait object upcastable to `U`.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate refmove;
+/// use std::any::Any;
+/// use std::fmt::Debug;
+/// use refmove::{Anchor, AnchorExt, RefMove};
+///
+/// # fn main() {
+/// let mut anchor = [1, 2, 3].anchor();
+/// let x = unsize_move!((anchor.borrow_move()) as [i32]);
+/// assert_eq!(x.len(), 3);
+///
+/// // Auto traits can be added to the trait object.
+/// let mut anchor = 42.anchor();
+/// let x = unsize_move!((anchor.borrow_move()) as dyn Any + Send);
+/// // And supertraits can be taken out of the trait object.
+/// trait Object: Debug + Send {}
+/// impl Object for String {}
+/// let mut anchor = "hoge".to_string().anchor();
+/// let y = unsize_move!((anchor.borrow_move()) as dyn Object);
+/// let y: RefMove<dyn Debug> = unsize_move!(y as dyn Debug);
+/// # let _ = (x, y);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! unsize_move {
+    ($e:tt as $U:ty) => {
+        {
+            #[allow(unused_parens)]
+            let e = $e;
+            unsafe { $crate::__UnsizeMove::unsize_move(e, |p| -> *mut $U { p }) }
+        }
+    };
+}
+
+/// Implementation detail of `unsize_move!`.
+#[doc(hidden)]
+pub trait UnsizeMove<'a>: Sized {
+    type Pointee: ?Sized + 'a;
+    type Output<U: ?Sized + 'a>;
+
+    /// Converts the pointer with `f`.
+    ///
+    /// ## Safety
+    ///
+    /// `f` must return the given pointer itself, only changing its metadata.
+    unsafe fn unsize_move<U, F>(self, f: F) -> Self::Output<U>
+    where
+        U: ?Sized + 'a,
+        F: FnOnce(*mut Self::Pointee) -> *mut U;
+}
+
+impl<'a, T: ?Sized + 'a> UnsizeMove<'a> for RefMove<'a, T> {
+    type Pointee = T;
+    type Output<U: ?Sized + 'a> = RefMove<'a, U>;
+
+    unsafe fn unsize_move<U, F>(self, f: F) -> RefMove<'a, U>
+    where
+        U: ?Sized + 'a,
+        F: FnOnce(*mut T) -> *mut U,
+    {
+        RefMove::from_ptr(f(RefMove::into_ptr(self)))
+    }
+}
+
+impl<'a, T: ?Sized + 'a> UnsizeMove<'a> for Pin<RefMove<'a, T>> {
+    type Pointee = T;
+    type Output<U: ?Sized + 'a> = Pin<RefMove<'a, U>>;
+
+    unsafe fn unsize_move<U, F>(self, f: F) -> Pin<RefMove<'a, U>>
+    where
+        U: ?Sized + 'a,
+        F: FnOnce(*mut T) -> *mut U,
+    {
+        // The address does not change, so the content stays pinned.
+        Pin::new_unchecked(Pin::into_inner_unchecked(self).unsize_move(f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::fmt::Debug;
+
+    use test_util::DropCount;
+    use {Anchor, AnchorExt, RefMove};
+
+    trait Object: Debug {}
+    impl<'a> Object for DropCount<'a> {}
+
+    #[test]
+    fn test_unsize_move() {
+        let count = Cell::new(0);
+        let mut anchor = [DropCount(&count), DropCount(&count)].anchor();
+        let x: RefMove<[DropCount]> = unsize_move!((anchor.borrow_move()) as [_]);
+        assert_eq!(x.len(), 2);
+        drop(x);
+        assert_eq!(count.get(), 2);
+
+        let mut anchor = DropCount(&count).anchor();
+        let x = unsize_move!((anchor.borrow_move()) as dyn Object);
+        let x: RefMove<dyn Debug> = unsize_move!(x as dyn Debug);
+        assert_eq!(format!("{:?}", x), "DropCount(Cell { value: 2 })");
+        drop(x);
+        assert_eq!(count.get(), 3);
+    }
+}
@@ -0,0 +1,295 @@
+//! By-move draining of `Vec` and `VecDeque`.
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use std::fmt;
+use std::iter::FusedIterator;
+#[cfg(feature = "nightly")]
+use std::iter::TrustedLen;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+use std::ptr;
+
+use RefMove;
+
+/// Provides `drain_move` method for `Vec<T>` and `VecDeque<T>`.
+pub trait DrainMove<T> {
+    /// Lends the elements in `range` by move to `f` without moving them out
+    /// of the buffer, and removes them.
+    ///
+    /// Unlike `drain`, the iterator given to `f` yields `RefMove`s pointing
+    /// into the buffer. When `f` returns or panics, the elements not yielded
+    /// are dropped in place and the elements after `range` are moved to
+    /// fill the gap.
+    ///
+    /// ```rust
+    /// extern crate refmove;
+    /// use refmove::DrainMove;
+    ///
+    /// # fn main() {
+    /// let mut v = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    /// let lens = v.drain_move(..2, |iter| iter.map(|x| x.len()).sum::<usize>());
+    /// assert_eq!(lens, 2);
+    /// assert_eq!(v, ["c"]);
+    /// # }
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the range is decreasing or out of bounds.
+    fn drain_move<Rg, R, F>(&mut self, range: Rg, f: F) -> R
+    where
+        Rg: RangeBounds<usize>,
+        F: for<'a> FnOnce(&mut Drain<'a, T>) -> R;
+}
+
+impl<T> DrainMove<T> for Vec<T> {
+    fn drain_move<Rg, R, F>(&mut self, range: Rg, f: F) -> R
+    where
+        Rg: RangeBounds<usize>,
+        F: for<'a> FnOnce(&mut Drain<'a, T>) -> R,
+    {
+        let len = self.len();
+        let (start, end) = to_range(range, len);
+        let ptr = self.as_mut_ptr();
+        unsafe {
+            // Until the gap is filled, the vector only owns the head.
+            self.set_len(start);
+        }
+        let _fill = FillVec {
+            vec: self,
+            start,
+            end,
+            len,
+        };
+        // Dropped before `_fill`.
+        let mut drain = Drain {
+            ptr,
+            start,
+            end,
+            _marker: PhantomData,
+        };
+        f(&mut drain)
+    }
+}
+
+impl<T> DrainMove<T> for VecDeque<T> {
+    fn drain_move<Rg, R, F>(&mut self, range: Rg, f: F) -> R
+    where
+        Rg: RangeBounds<usize>,
+        F: for<'a> FnOnce(&mut Drain<'a, T>) -> R,
+    {
+        // Makes the buffer contiguous, without reallocation.
+        let mut restore = RestoreVecDeque {
+            vec: Vec::from(mem::take(self)),
+            deque: self,
+        };
+        restore.vec.drain_move(range, f)
+    }
+}
+
+/// Moves the tail of a `Vec` over the drained elements.
+struct FillVec<'a, T: 'a> {
+    vec: &'a mut Vec<T>,
+    start: usize,
+    end: usize,
+    len: usize,
+}
+
+impl<'a, T: 'a> Drop for FillVec<'a, T> {
+    fn drop(&mut self) {
+        unsafe {
+            let ptr = self.vec.as_mut_ptr();
+            ptr::copy(ptr.add(self.end), ptr.add(self.start), self.len - self.end);
+            self.vec.set_len(self.len - (self.end - self.start));
+        }
+    }
+}
+
+/// Puts the drained `Vec` back into the `VecDeque`.
+struct RestoreVecDeque<'a, T: 'a> {
+    deque: &'a mut VecDeque<T>,
+    vec: Vec<T>,
+}
+
+impl<'a, T: 'a> Drop for RestoreVecDeque<'a, T> {
+    fn drop(&mut self) {
+        *self.deque = VecDeque::from(mem::take(&mut self.vec));
+    }
+}
+
+/// Converts `range` into `start..end`, checking it against `len`.
+fn to_range<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1).expect("range start overflow"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1).expect("range end overflow"),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    assert!(start <= end, "range start is greater than range end");
+    assert!(end <= len, "range end is out of bounds");
+    (start, end)
+}
+
+/// An iterator over the drained elements of a `Vec` or `VecDeque`,
+/// as by-move references into its buffer.
+///
+/// This `struct` is lent by [`DrainMove::drain_move`].
+/// Elements not yet yielded are dropped in place after the borrowing
+/// closure returns.
+///
+/// [`DrainMove::drain_move`]: trait.DrainMove.html#tymethod.drain_move
+pub struct Drain<'a, T: 'a> {
+    ptr: *mut T,
+    start: usize,
+    end: usize,
+    _marker: PhantomData<(&'a (), T)>,
+}
+
+unsafe impl<'a, T: Send + 'a> Send for Drain<'a, T> {}
+unsafe impl<'a, T: Sync + 'a> Sync for Drain<'a, T> {}
+
+impl<'a, T: 'a> Drain<'a, T> {
+    fn ptr(&self, i: usize) -> *mut T {
+        unsafe { self.ptr.add(i) }
+    }
+
+    /// Returns the remaining items of this iterator as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { &*self.raw_slice() }
+    }
+
+    fn raw_slice(&self) -> *mut [T] {
+        ptr::slice_from_raw_parts_mut(self.ptr(self.start), self.end - self.start)
+    }
+}
+
+impl<'a, T: 'a> Iterator for Drain<'a, T> {
+    type Item = RefMove<'a, T>;
+    fn next(&mut self) -> Option<RefMove<'a, T>> {
+        if self.start == self.end {
+            None
+        } else {
+            let ret = unsafe { RefMove::from_ptr(self.ptr(self.start)) };
+            self.start += 1;
+            Some(ret)
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+    fn count(self) -> usize {
+        self.len()
+    }
+}
+
+impl<'a, T: 'a> DoubleEndedIterator for Drain<'a, T> {
+    fn next_back(&mut self) -> Option<RefMove<'a, T>> {
+        if self.start == self.end {
+            None
+        } else {
+            self.end -= 1;
+            Some(unsafe { RefMove::from_ptr(self.ptr(self.end)) })
+        }
+    }
+}
+
+impl<'a, T: 'a> ExactSizeIterator for Drain<'a, T> {
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+    #[cfg(feature = "nightly")]
+    fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl<'a, T: 'a> FusedIterator for Drain<'a, T> {}
+#[cfg(feature = "nightly")]
+unsafe impl<'a, T: 'a> TrustedLen for Drain<'a, T> {}
+
+impl<'a, T: 'a> Drop for Drain<'a, T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.raw_slice());
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug + 'a> fmt::Debug for Drain<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Drain").field(&self.as_slice()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::collections::VecDeque;
+
+    use super::DrainMove;
+    use test_util::DropCount;
+
+    /// An element that counts its drops.
+    struct Item<'a> {
+        value: i32,
+        _count: DropCount<'a>,
+    }
+
+    fn item<'a>(count: &'a Cell<usize>, value: i32) -> Item<'a> {
+        Item {
+            value,
+            _count: DropCount(count),
+        }
+    }
+
+    #[test]
+    fn test_drain_move_vec() {
+        let count = Cell::new(0);
+        let mut v: Vec<_> = (0..5).map(|i| item(&count, i)).collect();
+        let first = v.drain_move(1..4, |iter| {
+            assert_eq!(iter.len(), 3);
+            let first = iter.next().unwrap().value;
+            assert_eq!(count.get(), 1);
+            first
+        });
+        assert_eq!(first, 1);
+        assert_eq!(count.get(), 3);
+        assert_eq!(v.iter().map(|x| x.value).collect::<Vec<_>>(), [0, 4]);
+    }
+
+    #[test]
+    fn test_drain_move_vec_deque() {
+        let count = Cell::new(0);
+        let mut v: VecDeque<_> = (2..5).map(|i| item(&count, i)).collect();
+        v.push_front(item(&count, 1));
+        v.push_front(item(&count, 0));
+        let drained = v.drain_move(1.., |iter| {
+            iter.rev().take(3).map(|x| x.value).collect::<Vec<_>>()
+        });
+        assert_eq!(drained, [4, 3, 2]);
+        assert_eq!(count.get(), 4);
+        assert_eq!(v.iter().map(|x| x.value).collect::<Vec<_>>(), [0]);
+    }
+
+    #[test]
+    fn test_drain_move_boxes() {
+        let mut v: VecDeque<_> = (2..6).map(Box::new).collect();
+        v.push_front(Box::new(1));
+        v.push_front(Box::new(0));
+        let drained = v.drain_move(1..4, |iter| iter.next().map(|x| **x));
+        assert_eq!(drained, Some(1));
+        assert_eq!(v, [Box::new(0), Box::new(4), Box::new(5)]);
+
+        let mut v: Vec<_> = (0..4).map(Box::new).collect();
+        let drained = v.drain_move(..2, |iter| iter.next_back().map(|x| **x));
+        assert_eq!(drained, Some(1));
+        assert_eq!(v, [Box::new(2), Box::new(3)]);
+    }
+}
@@ -0,0 +1,62 @@
+//! Checks the optimized code of `examples/codegen.rs`.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Builds the example in release mode and returns its LLVM IR.
+fn emit_llvm_ir() -> String {
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("codegen");
+    let status = Command::new(env!("CARGO"))
+        .args(["rustc", "--release", "--example", "codegen", "--target-dir"])
+        .arg(&target_dir)
+        .args(["--", "--emit=llvm-ir"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let examples = target_dir.join("release").join("examples");
+    let ir = fs::read_dir(&examples)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            name.starts_with("codegen-") && name.ends_with(".ll")
+        })
+        .max_by_key(|path| path.metadata().unwrap().modified().unwrap())
+        .unwrap();
+    fs::read_to_string(ir).unwrap()
+}
+
+/// Extracts the body of the function named `name`.
+fn function<'a>(ir: &'a str, name: &str) -> &'a str {
+    let header = format!("@{}(", name);
+    let start = ir
+        .match_indices("\ndefine ")
+        .map(|(pos, _)| pos + 1)
+        .find(|&pos| ir[pos..].lines().next().unwrap().contains(&header))
+        .unwrap_or_else(|| panic!("{} not found", name));
+    let end = start + ir[start..].find("\n}\n").unwrap();
+    &ir[start..end]
+}
+
+#[test]
+fn test_codegen() {
+    let ir = emit_llvm_ir();
+
+    // The anchor is inlined away and the content is passed directly.
+    let anchor = function(&ir, "codegen_anchor");
+    assert!(anchor.contains("@codegen_consume("), "{}", anchor);
+    assert!(!anchor.contains("panic"), "{}", anchor);
+
+    // Without the drop flag, nothing is left but the call.
+    let let_move = function(&ir, "codegen_let_move");
+    assert!(
+        let_move.contains("call void @codegen_consume("),
+        "{}",
+        let_move
+    );
+    assert!(!let_move.contains("panic"), "{}", let_move);
+    assert!(!let_move.contains(" br "), "{}", let_move);
+    assert!(!let_move.contains("landingpad"), "{}", let_move);
+}
@@ -0,0 +1,88 @@
+#[macro_use]
+extern crate refmove_derive;
+extern crate refmove;
+
+use std::cell::Cell;
+
+use refmove::{Anchor, AnchorExt, RefMove};
+
+#[derive(Debug)]
+struct DropCount<'a>(&'a Cell<usize>);
+
+impl<'a> Drop for DropCount<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[derive(MoveFields)]
+struct Named<'a, T> {
+    name: String,
+    /// Docs are carried over.
+    value: T,
+    count: DropCount<'a>,
+}
+
+#[derive(MoveFields)]
+struct Tuple<T>(String, T);
+
+#[test]
+fn test_named() {
+    let count = Cell::new(0);
+    let mut anchor = Named {
+        name: "hoge".to_string(),
+        value: vec![1, 2, 3],
+        count: DropCount(&count),
+    }
+    .anchor();
+    let x: RefMove<Named<Vec<i32>>> = anchor.borrow_move();
+    let NamedFields {
+        name,
+        value,
+        count: c,
+    }: NamedFields<Vec<i32>> = x.into_fields();
+    assert_eq!(name.into_inner(), "hoge");
+    assert_eq!(value.into_inner(), [1, 2, 3]);
+    assert_eq!(count.get(), 0);
+    drop(c);
+    assert_eq!(count.get(), 1);
+    drop(anchor);
+    assert_eq!(count.get(), 1);
+}
+
+#[test]
+fn test_tuple() {
+    let mut anchor = Tuple("fuga".to_string(), 42).anchor();
+    let TupleFields(s, n) = anchor.borrow_move().into_fields();
+    assert_eq!(s.into_inner(), "fuga");
+    assert_eq!(*n, 42);
+}
+
+#[derive(MoveFields)]
+enum State<T> {
+    Idle,
+    Running(String, T),
+    Done { result: T },
+}
+
+#[test]
+fn test_enum() {
+    fn f(x: RefMove<State<Vec<i32>>>) -> Vec<i32> {
+        match x.into_fields() {
+            StateFields::Idle => vec![],
+            StateFields::Running(_, progress) => progress.into_inner(),
+            StateFields::Done { result } => result.into_inner(),
+        }
+    }
+    assert_eq!(f(State::Idle.anchor().borrow_move()), []);
+    assert_eq!(
+        f(State::Running("hoge".to_string(), vec![1])
+            .anchor()
+            .borrow_move()),
+        [1]
+    );
+    assert_eq!(
+        f(State::Done { result: vec![2] }.anchor().borrow_move()),
+        [2]
+    );
+}